@@ -0,0 +1,51 @@
+// Arbitrary streams of the XML events of a docx main part, balanced or
+// not: each byte picks an event among the elements the parser knows.
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate mso_to_text;
+extern crate xml;
+
+use std::io;
+
+use xml::attribute::OwnedAttribute;
+use xml::name::OwnedName;
+use xml::namespace::Namespace;
+use xml::reader::XmlEvent;
+
+use mso_to_text::mso_doc;
+use mso_to_text::converters::TextConverter;
+
+
+const TAGS: &[&str] = &[
+    "document", "body", "p", "pPr", "pStyle", "sectPr", "r", "t",
+    "tbl", "tr", "trPr", "tblHeader", "tc", "footnote", "endnote", "txbxContent"
+];
+
+fn name(tag: &str) -> OwnedName {
+    OwnedName {
+        local_name: tag.to_owned(),
+        namespace: Some("http://schemas.openxmlformats.org/wordprocessingml/2006/main".to_owned()),
+        prefix: Some("w".to_owned())
+    }
+}
+
+fn event(byte: u8) -> XmlEvent {
+    let tag = TAGS[(byte & 0x0F) as usize];
+    match byte >> 4 {
+        0..=5 => XmlEvent::StartElement {
+            name: name(tag),
+            attributes: vec![OwnedAttribute { name: name("val"), value: "true".to_owned() }],
+            namespace: Namespace::empty()
+        },
+        6..=11 => XmlEvent::EndElement { name: name(tag) },
+        12..=14 => XmlEvent::Characters(tag.repeat(byte as usize % 3 + 1)),
+        _ => XmlEvent::Whitespace(" ".to_owned())
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut events = data.iter().map(|&byte| Ok(event(byte)));
+    let mut converter = TextConverter::new(io::sink());
+    let _ = mso_doc::parse_docx_events(&mut events, &mso_doc::Options::default(), &mut converter);
+});
@@ -0,0 +1,21 @@
+// Any bytes as an uploaded document, whatever format they are sniffed as.
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate mso_to_text;
+
+use std::io;
+
+use mso_to_text::mso_doc;
+use mso_to_text::converters::TextConverter;
+
+fuzz_target!(|data: &[u8]| {
+    let mut converter = TextConverter::new(io::sink());
+    let _ = mso_doc::parse_reader(io::Cursor::new(data), &mso_doc::Options::default(), &mut converter);
+
+    for event in mso_doc::events(io::Cursor::new(data), &mso_doc::Options::default()) {
+        if event.is_err() {
+            break;
+        }
+    }
+});
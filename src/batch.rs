@@ -0,0 +1,277 @@
+extern crate glob;
+
+use std::io;
+use std::io::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::collections::BTreeMap;
+use std::sync::{mpsc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use self::glob::Pattern;
+
+
+// Extensions looked for in directories when no include pattern is given.
+const DOCUMENT_EXTENSIONS: &[&str] = &["docx", "docm", "dotx", "dotm", "doc", "odt", "ods", "rtf", "xml"];
+
+
+// Document to convert, with its path relative to the directory given on
+// the command line, or its file name when given directly.
+pub struct Input {
+    pub path: PathBuf,
+    pub relative: PathBuf
+}
+
+impl Input {
+    // Path of the converted document, mirroring the input tree.
+    pub fn output_path(&self, output_dir: &Path, extension: &str) -> PathBuf {
+        output_dir.join(&self.relative).with_extension(extension)
+    }
+}
+
+
+// First two inputs converted to the same path of the output directory,
+// as documents of the same name given from different directories, or
+// differing only by their extension.
+pub fn same_output<'a>(inputs: &'a [Input], output_dir: &Path, extension: &str) -> Option<(&'a Input, &'a Input, PathBuf)> {
+    let mut outputs: BTreeMap<PathBuf, &Input> = BTreeMap::new();
+    for input in inputs {
+        let output_path = input.output_path(output_dir, extension);
+        if let Some(previous) = outputs.get(&output_path) {
+            return Some((previous, input, output_path));
+        }
+        outputs.insert(output_path, input);
+    }
+    None
+}
+
+
+// Filters on the files found in directories, matched against their
+// relative path.
+pub struct Selection {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>
+}
+
+impl Selection {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Selection, glob::PatternError> {
+        Ok(Selection {
+            include: include.iter().map(|p| Pattern::new(p)).collect::<Result<_, _>>()?,
+            exclude: exclude.iter().map(|p| Pattern::new(p)).collect::<Result<_, _>>()?
+        })
+    }
+
+    fn is_selected(&self, relative: &Path) -> bool {
+        let included = if self.include.is_empty() {
+            relative.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| DOCUMENT_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        } else {
+            self.include.iter().any(|p| p.matches_path(relative))
+        };
+        included && !self.exclude.iter().any(|p| p.matches_path(relative))
+    }
+
+    // Documents of the given paths, directories being walked recursively
+    // in name order, and the paths which could not be read. Links to
+    // directories are not followed, as find does, for a link to a parent
+    // not to walk the tree forever.
+    pub fn collect(&self, paths: &[String]) -> (Vec<Input>, Vec<(PathBuf, io::Error)>) {
+        let mut inputs = Vec::new();
+        let mut failures = Vec::new();
+
+        for path in paths.iter().map(PathBuf::from) {
+            if path.is_dir() {
+                self.walk(&path, Path::new(""), &mut inputs, &mut failures);
+            } else {
+                let relative = PathBuf::from(path.file_name().unwrap_or(path.as_os_str()));
+                inputs.push(Input { path, relative });
+            }
+        }
+        (inputs, failures)
+    }
+
+    fn walk(&self, root: &Path, relative: &Path,
+            inputs: &mut Vec<Input>, failures: &mut Vec<(PathBuf, io::Error)>) {
+        let directory = root.join(relative);
+        let mut entries = match fs::read_dir(&directory).and_then(|dir| dir.collect::<io::Result<Vec<_>>>()) {
+            Ok(entries) => entries,
+            Err(err) => return failures.push((directory, err))
+        };
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            let relative = relative.join(entry.file_name());
+            let path = entry.path();
+            let file_type = match entry.file_type() {
+                Ok(file_type) => file_type,
+                Err(err) => {
+                    failures.push((path, err));
+                    continue;
+                }
+            };
+            if file_type.is_dir() {
+                self.walk(root, &relative, inputs, failures);
+            } else if file_type.is_symlink() && path.is_dir() {
+                continue;
+            } else if self.is_selected(&relative) {
+                inputs.push(Input { path, relative });
+            }
+        }
+    }
+}
+
+
+// Converts the inputs, given with their index, on `jobs` threads, each
+// taking the next input left, and reports the outcomes in the order of the
+// inputs whatever the order they complete in.
+pub fn run<T, C, R>(inputs: &[Input], jobs: usize, convert: C, mut report: R)
+    where T: Send, C: Fn(usize, &Input) -> T + Sync, R: FnMut(&Input, T) {
+        let next = AtomicUsize::new(0);
+        let (sender, receiver) = mpsc::channel();
+
+        thread::scope(|scope| {
+            for _ in 0..jobs.clamp(1, inputs.len().max(1)) {
+                let sender = sender.clone();
+                let (next, convert) = (&next, &convert);
+                scope.spawn(move || loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= inputs.len() || sender.send((i, convert(i, &inputs[i]))).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(sender);
+
+            let mut completed = BTreeMap::new();
+            let mut reported = 0;
+            for (i, outcome) in receiver {
+                completed.insert(i, outcome);
+                while let Some(outcome) = completed.remove(&reported) {
+                    report(&inputs[reported], outcome);
+                    reported += 1;
+                }
+            }
+        });
+    }
+
+
+// Output shared by the conversions and written in the order of the
+// inputs: the first input not written yet goes straight to it, the others
+// are kept until their turn. Each input starts with its header on stderr.
+pub struct OrderedOutput<W: Write> {
+    state: Mutex<Ordered<W>>
+}
+
+struct Ordered<W: Write> {
+    out: W,
+    next: usize,
+    finished: BTreeMap<usize, (String, Vec<u8>)>,
+    // first write error, failing the later writes
+    error: Option<io::Error>
+}
+
+impl<W: Write> Ordered<W> {
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        if self.error.is_none() {
+            if let Err(err) = self.out.write_all(buf) {
+                self.error = Some(err);
+            }
+        }
+        match self.error {
+            Some(ref err) => Err(io::Error::new(err.kind(), err.to_string())),
+            None => Ok(())
+        }
+    }
+}
+
+impl<W: Write> OrderedOutput<W> {
+    pub fn new(out: W) -> OrderedOutput<W> {
+        OrderedOutput { state: Mutex::new(Ordered { out, next: 0, finished: BTreeMap::new(), error: None }) }
+    }
+
+    pub fn slot(&self, index: usize, header: String) -> Slot<'_, W> {
+        Slot { output: self, index, header: Some(header), buffer: Vec::new() }
+    }
+
+    // First error writing on the output, or of its final flush.
+    pub fn finish(self) -> io::Result<()> {
+        let mut state = self.state.into_inner().unwrap_or_else(|err| err.into_inner());
+        match state.error.take() {
+            Some(err) => Err(err),
+            None => state.out.flush()
+        }
+    }
+
+    // Error writing on the output, met by a conversion so far.
+    pub fn error(&self) -> Option<io::Error> {
+        let state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        state.error.as_ref().map(|err| io::Error::new(err.kind(), err.to_string()))
+    }
+}
+
+// Part of the shared output of an input.
+pub struct Slot<'a, W: Write + 'a> {
+    output: &'a OrderedOutput<W>,
+    index: usize,
+    // written before the content once the input has its turn
+    header: Option<String>,
+    buffer: Vec<u8>
+}
+
+impl<'a, W: Write> Slot<'a, W> {
+    // Ends the input, writing the next ones finished already.
+    pub fn finish(mut self) -> io::Result<()> {
+        let mut state = self.output.state.lock().unwrap_or_else(|err| err.into_inner());
+        if state.next != self.index {
+            let header = self.header.take().unwrap_or_default();
+            state.finished.insert(self.index, (header, self.buffer.split_off(0)));
+            return Ok(());
+        }
+
+        self.start(&mut state)?;
+        state.next += 1;
+        loop {
+            let next = state.next;
+            match state.finished.remove(&next) {
+                Some((header, content)) => {
+                    eprintln!("{}", header);
+                    state.write_all(&content)?;
+                    state.next += 1;
+                },
+                None => break
+            }
+        }
+        state.out.flush()
+    }
+
+    // Header and content kept so far of the input having its turn.
+    fn start(&mut self, state: &mut Ordered<W>) -> io::Result<()> {
+        if let Some(header) = self.header.take() {
+            eprintln!("{}", header);
+        }
+        if !self.buffer.is_empty() {
+            state.write_all(&self.buffer)?;
+            self.buffer.clear();
+        }
+        Ok(())
+    }
+}
+
+impl<'a, W: Write> Write for Slot<'a, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.output.state.lock().unwrap_or_else(|err| err.into_inner());
+        if state.next == self.index {
+            self.start(&mut state)?;
+            state.write_all(buf)?;
+        } else {
+            self.buffer.extend_from_slice(buf);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+// Chunks
+// Passages of a document for search indexing, each under the path of its
+// nearest headings:
+//   {"document":"contract.docx","index":3,"heading_path":"Contract > 3. Payment > 3.1 Terms","text":"..."}
+// A passage holds the paragraphs and table rows following a heading, up
+// to the maximum size, split between paragraphs or rows only: a single
+// paragraph longer than the maximum makes a chunk of its own.
+extern crate serde;
+extern crate serde_json;
+
+use std::io;
+use std::io::Write;
+
+use self::serde::Serialize;
+
+use document::{Block, Document};
+
+
+const PATH_SEPARATOR: &str = " > ";
+
+
+#[derive(Debug, Serialize)]
+pub struct Chunk {
+    pub index: usize,
+    pub heading_path: String,
+    pub text: String
+}
+
+// Chunks of at most max_size characters, empty passages left out.
+pub fn chunks(document: &Document, max_size: usize) -> Vec<Chunk> {
+    let mut builder = ChunkBuilder {
+        chunks: Vec::new(),
+        headings: Vec::new(),
+        text: String::new(),
+        size: 0,
+        max_size
+    };
+
+    for block in document.blocks() {
+        match *block {
+            Block::Paragraph { level: Some(level), .. } => {
+                builder.close();
+                while builder.headings.last().is_some_and(|&(parent, _)| parent >= level) {
+                    builder.headings.pop();
+                }
+                builder.headings.push((level, block.text()));
+            },
+            Block::Paragraph { .. } => builder.push(&block.text()),
+            Block::Table { ref rows } => {
+                for row in rows {
+                    let cells: Vec<&str> = row.cells.iter().map(|cell| cell.text.as_str()).collect();
+                    builder.push(&cells.join("\t"));
+                }
+            }
+        }
+    }
+    builder.close();
+    builder.chunks
+}
+
+// One JSON object per line and per chunk, with the name of the document.
+pub fn write_json_lines<W: Write>(chunks: &[Chunk], document: &str, out: &mut W) -> io::Result<()> {
+    #[derive(Serialize)]
+    struct Line<'a> {
+        document: &'a str,
+        #[serde(flatten)]
+        chunk: &'a Chunk
+    }
+
+    for chunk in chunks {
+        serde_json::to_writer(&mut *out, &Line { document, chunk }).map_err(io::Error::from)?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+
+struct ChunkBuilder {
+    chunks: Vec<Chunk>,
+    // level and text of the headings enclosing the text
+    headings: Vec<(u32, String)>,
+    text: String,
+    // characters of the text
+    size: usize,
+    max_size: usize
+}
+
+impl ChunkBuilder {
+    fn push(&mut self, text: &str) {
+        if text.trim().is_empty() {
+            return;
+        }
+        let size = text.chars().count();
+        if self.size > 0 && self.size + 1 + size > self.max_size {
+            self.close();
+        }
+        if self.size > 0 {
+            self.text.push('\n');
+            self.size += 1;
+        }
+        self.text.push_str(text);
+        self.size += size;
+    }
+
+    fn close(&mut self) {
+        if self.text.is_empty() {
+            return;
+        }
+        let heading_path = self.headings.iter()
+            .map(|(_, text)| text.as_str())
+            .collect::<Vec<_>>()
+            .join(PATH_SEPARATOR);
+        self.chunks.push(Chunk {
+            index: self.chunks.len(),
+            heading_path,
+            text: self.text.split_off(0)
+        });
+        self.size = 0;
+    }
+}
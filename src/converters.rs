@@ -0,0 +1,142 @@
+// Converters writing the events of a document on any writer: stdout, a
+// file, a Vec<u8> or a socket.
+use std::io;
+use std::io::Write;
+
+use mso_doc;
+use output::Output;
+
+
+// One line per paragraph, style then text, and one line per table row,
+// cells prefixed with their column number. The text of a paragraph of a
+// role is marked as in plain text mail: a list item after a dash, a quote
+// after a chevron, code indented by four spaces and a caption by two.
+pub struct TextConverter<W: Write> {
+    output: Output<W>,
+    started_table_row: bool,
+    column_i: u32
+}
+
+impl<W: Write> TextConverter<W> {
+    pub fn new(out: W) -> TextConverter<W> {
+        TextConverter { output: Output::new(out), started_table_row: false, column_i: 0 }
+    }
+
+    // Flushed writer, or the first write error.
+    pub fn finish(self) -> io::Result<W> {
+        self.output.finish()
+    }
+}
+
+impl<W: Write> mso_doc::WordReader for TextConverter<W> {
+    fn paragraph_row(&mut self, text: &str, style: &str) {
+        self.output.write(format_args!("{}\t{}\n", style, text));
+    }
+
+    fn role_row(&mut self, text: &str, style: &str, role: mso_doc::Role) {
+        let mark = match role {
+            mso_doc::Role::List => "- ",
+            mso_doc::Role::Quote => "> ",
+            mso_doc::Role::Code => "    ",
+            mso_doc::Role::Caption => "  ",
+            mso_doc::Role::Heading(_) | mso_doc::Role::Ignore => ""
+        };
+        self.output.write(format_args!("{}\t{}{}\n", style, mark, text));
+    }
+
+    fn table_new_row(&mut self) {
+        if self.started_table_row {
+            self.output.write(format_args!("\n"));
+        }
+        self.column_i = 0;
+        self.started_table_row = false;
+    }
+
+    fn table_closed(&mut self) {
+        if self.started_table_row {
+            self.output.write(format_args!("\n"));
+        }
+    }
+
+    fn table_cell(&mut self, text: &str, style: &str, header: bool) {
+        self.started_table_row = true;
+        self.column_i += 1;
+        self.output.write(format_args!("|{}{} {}\t{}",
+               if header { "#" } else { "" },
+               self.column_i,
+               style,
+               text));
+    }
+}
+
+// Only tables are written, one record per row and an empty line between
+// two tables.
+pub struct CsvConverter<W: Write> {
+    output: Output<W>,
+    row: Vec<String>,
+    in_table: bool,
+    in_row: bool,
+    written_tables: u32
+}
+
+impl<W: Write> CsvConverter<W> {
+    pub fn new(out: W) -> CsvConverter<W> {
+        CsvConverter {
+            output: Output::new(out),
+            row: Vec::new(),
+            in_table: false,
+            in_row: false,
+            written_tables: 0
+        }
+    }
+
+    // Flushed writer, or the first write error.
+    pub fn finish(self) -> io::Result<W> {
+        self.output.finish()
+    }
+
+    fn quote(field: &str) -> String {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_owned()
+        }
+    }
+
+    fn flush_row(&mut self) {
+        if self.in_row {
+            let fields: Vec<String> = self.row.iter().map(|field| Self::quote(field)).collect();
+            self.output.write(format_args!("{}\n", fields.join(",")));
+        }
+        self.row.clear();
+        self.in_row = false;
+    }
+}
+
+impl<W: Write> mso_doc::WordReader for CsvConverter<W> {
+    fn paragraph_row(&mut self, _text: &str, _style: &str) {
+    }
+
+    fn table_new_row(&mut self) {
+        if !self.in_table {
+            if self.written_tables > 0 {
+                self.output.write(format_args!("\n"));
+            }
+            self.in_table = true;
+        }
+        self.flush_row();
+        self.in_row = true;
+    }
+
+    fn table_closed(&mut self) {
+        self.flush_row();
+        if self.in_table {
+            self.written_tables += 1;
+        }
+        self.in_table = false;
+    }
+
+    fn table_cell(&mut self, text: &str, _style: &str, _header: bool) {
+        self.row.push(text.to_owned());
+    }
+}
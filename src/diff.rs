@@ -0,0 +1,404 @@
+// Diff
+// Differences between two versions of a document, compared paragraph by
+// paragraph, a table cell being a paragraph of its own. Text is compared
+// with its spaces collapsed, so that formatting differences splitting the
+// runs or changing the spacing are none. A changed paragraph similar
+// enough to its old version has its changed words marked, as git does:
+//   -The buyer pays within [-30-] days.
+//   +The buyer pays within {+60+} days.
+use std::cmp;
+use std::io;
+use std::io::Write;
+
+use mso_doc;
+
+
+// Share of common words for a deleted and an inserted paragraph to be
+// shown as a changed one.
+const MIN_SIMILARITY: f64 = 0.5;
+
+
+#[derive(Debug, PartialEq)]
+pub enum Change {
+    Equal(String),
+    Deleted(String),
+    Inserted(String),
+    // old and new text, their changed words marked
+    Changed(String, String)
+}
+
+impl Change {
+    fn in_old(&self) -> bool {
+        !matches!(*self, Change::Inserted(_))
+    }
+
+    fn in_new(&self) -> bool {
+        !matches!(*self, Change::Deleted(_))
+    }
+}
+
+
+// Reader collecting the non empty paragraphs and cells of a document.
+#[derive(Default)]
+pub struct Paragraphs {
+    paragraphs: Vec<String>
+}
+
+impl Paragraphs {
+    pub fn finish(self) -> Vec<String> {
+        self.paragraphs
+    }
+
+    fn push(&mut self, text: &str) {
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !text.is_empty() {
+            self.paragraphs.push(text);
+        }
+    }
+}
+
+impl mso_doc::WordReader for Paragraphs {
+    fn paragraph_row(&mut self, text: &str, _style: &str) {
+        self.push(text);
+    }
+
+    fn table_new_row(&mut self) {}
+
+    fn table_closed(&mut self) {}
+
+    fn table_cell(&mut self, text: &str, _style: &str, _header: bool) {
+        self.push(text);
+    }
+}
+
+
+// Changes from the old paragraphs to the new ones, deletions before
+// insertions in a block of changes.
+pub fn diff(old: &[String], new: &[String]) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let mut deleted = Vec::new();
+    let mut inserted = Vec::new();
+
+    for op in common_subsequence(old, new) {
+        match op {
+            Op::Equal(i, _) => {
+                pair_changes(&mut deleted, &mut inserted, &mut changes);
+                changes.push(Change::Equal(old[i].clone()));
+            },
+            Op::Deleted(i) => deleted.push(&old[i]),
+            Op::Inserted(j) => inserted.push(&new[j])
+        }
+    }
+    pair_changes(&mut deleted, &mut inserted, &mut changes);
+    changes
+}
+
+// Old and new text with the deleted words marked [-...-] in the first,
+// and the inserted ones {+...+} in the second.
+pub fn word_diff(old: &str, new: &str) -> (String, String) {
+    let old_words: Vec<&str> = old.split_whitespace().collect();
+    let new_words: Vec<&str> = new.split_whitespace().collect();
+    let mut old_marked = Marked::new("[-", "-]");
+    let mut new_marked = Marked::new("{+", "+}");
+
+    for op in common_subsequence(&old_words, &new_words) {
+        match op {
+            Op::Equal(i, j) => {
+                old_marked.push(old_words[i], false);
+                new_marked.push(new_words[j], false);
+            },
+            Op::Deleted(i) => old_marked.push(old_words[i], true),
+            Op::Inserted(j) => new_marked.push(new_words[j], true)
+        }
+    }
+    (old_marked.finish(), new_marked.finish())
+}
+
+// Unified format, with the given number of unchanged paragraphs around
+// each block of changes:
+//   @@ -4,3 +4,4 @@
+pub fn write_unified<W: Write>(changes: &[Change], old_name: &str, new_name: &str, context: usize, out: &mut W)
+    -> io::Result<()> {
+        writeln!(out, "--- {}", old_name)?;
+        writeln!(out, "+++ {}", new_name)?;
+
+        for (start, end) in hunks(changes, context) {
+            let old_start = changes[..start].iter().filter(|change| change.in_old()).count();
+            let new_start = changes[..start].iter().filter(|change| change.in_new()).count();
+            let hunk = &changes[start..end];
+            let old_count = hunk.iter().filter(|change| change.in_old()).count();
+            let new_count = hunk.iter().filter(|change| change.in_new()).count();
+            writeln!(out, "@@ -{} +{} @@", range(old_start, old_count), range(new_start, new_count))?;
+
+            for change in hunk {
+                match *change {
+                    Change::Equal(ref text) => writeln!(out, " {}", text)?,
+                    Change::Deleted(ref text) => writeln!(out, "-{}", text)?,
+                    Change::Inserted(ref text) => writeln!(out, "+{}", text)?,
+                    Change::Changed(ref old, ref new) => {
+                        writeln!(out, "-{}", old)?;
+                        writeln!(out, "+{}", new)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+// Old and new paragraphs side by side, wrapped to columns fitting the
+// width, with a gutter as sdiff does: '|' changed, '<' deleted,
+// '>' inserted.
+pub fn write_side_by_side<W: Write>(changes: &[Change], width: usize, out: &mut W) -> io::Result<()> {
+    let column = cmp::max(width.saturating_sub(3) / 2, 10);
+
+    for change in changes {
+        let (old, gutter, new) = match *change {
+            Change::Equal(ref text) => (text.as_str(), ' ', text.as_str()),
+            Change::Deleted(ref text) => (text.as_str(), '<', ""),
+            Change::Inserted(ref text) => ("", '>', text.as_str()),
+            Change::Changed(ref old, ref new) => (old.as_str(), '|', new.as_str())
+        };
+        let old_lines = wrap(old, column);
+        let new_lines = wrap(new, column);
+
+        for i in 0..cmp::max(old_lines.len(), new_lines.len()) {
+            let left = old_lines.get(i).map_or("", String::as_str);
+            let right = new_lines.get(i).map_or("", String::as_str);
+            let gutter = if i == 0 { gutter } else { ' ' };
+            let line = format!("{}{} {} {}", left, " ".repeat(column - left.chars().count()), gutter, right);
+            writeln!(out, "{}", line.trim_end())?;
+        }
+    }
+    Ok(())
+}
+
+
+enum Op {
+    Equal(usize, usize),
+    Deleted(usize),
+    Inserted(usize)
+}
+
+// Edit script of a longest common subsequence, by the linear space
+// variant of Myers' algorithm: the middle snake of the shortest edit
+// script, searched from both ends at once, splits the sequences in two
+// halves compared in turn. It takes a time growing with the number of
+// changes, and a memory growing with the length of the sequences only.
+fn common_subsequence<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Op> {
+    let mut ops = Vec::with_capacity(old.len() + new.len());
+    let mut diagonals = Diagonals::new(old.len() + new.len());
+    edit_script(old, new, (0, 0), &mut diagonals, &mut ops);
+    ops
+}
+
+fn edit_script<T: PartialEq>(old: &[T], new: &[T], start: (usize, usize), diagonals: &mut Diagonals,
+                             ops: &mut Vec<Op>) {
+    let head = old.iter().zip(new).take_while(|&(a, b)| a == b).count();
+    let tail = old[head..].iter().rev().zip(new[head..].iter().rev()).take_while(|&(a, b)| a == b).count();
+    let old_middle = &old[head..old.len() - tail];
+    let new_middle = &new[head..new.len() - tail];
+    let (i, j) = (start.0 + head, start.1 + head);
+
+    ops.extend((0..head).map(|k| Op::Equal(start.0 + k, start.1 + k)));
+    if old_middle.is_empty() {
+        ops.extend((0..new_middle.len()).map(|k| Op::Inserted(j + k)));
+    } else if new_middle.is_empty() {
+        ops.extend((0..old_middle.len()).map(|k| Op::Deleted(i + k)));
+    } else {
+        let (x, y) = middle_snake(old_middle, new_middle, diagonals);
+        edit_script(&old_middle[..x], &new_middle[..y], (i, j), diagonals, ops);
+        edit_script(&old_middle[x..], &new_middle[y..], (i + x, j + y), diagonals, ops);
+    }
+    ops.extend((0..tail).map(|k| Op::Equal(old.len() - tail + start.0 + k, new.len() - tail + start.1 + k)));
+}
+
+// Start of the middle snake of sequences differing at both ends, a point
+// other than their start and end on a shortest edit path.
+fn middle_snake<T: PartialEq>(old: &[T], new: &[T], diagonals: &mut Diagonals) -> (usize, usize) {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+    let Diagonals { ref mut forward, ref mut backward, offset } = *diagonals;
+    // furthest x reached on each diagonal k = x - y, from the start for
+    // `forward`, from the end for `backward`
+    let at = |k: isize| (offset + k) as usize;
+    forward[at(1)] = 0;
+    backward[at(1)] = 0;
+
+    for d in 0..(n + m + 1) / 2 + 1 {
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = if k == -d || (k != d && forward[at(k - 1)] < forward[at(k + 1)]) {
+                forward[at(k + 1)]
+            } else {
+                forward[at(k - 1)] + 1
+            };
+            let (x0, y0) = (x, x - k);
+            let mut y = y0;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[at(k)] = x;
+            if odd && (k - delta).abs() < d && x + backward[at(delta - k)] >= n {
+                return (x0 as usize, y0 as usize);
+            }
+        }
+
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = if k == -d || (k != d && backward[at(k - 1)] < backward[at(k + 1)]) {
+                backward[at(k + 1)]
+            } else {
+                backward[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[(n - x - 1) as usize] == new[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[at(k)] = x;
+            if !odd && (k - delta).abs() <= d && x + forward[at(delta - k)] >= n {
+                return ((n - x) as usize, (m - y) as usize);
+            }
+        }
+    }
+    unreachable!("the paths from both ends meet")
+}
+
+// Furthest points of the paths searched by `middle_snake`, on the
+// diagonals from -offset to offset.
+struct Diagonals {
+    forward: Vec<isize>,
+    backward: Vec<isize>,
+    offset: isize
+}
+
+impl Diagonals {
+    fn new(size: usize) -> Diagonals {
+        let offset = size / 2 + 2;
+        Diagonals { forward: vec![0; 2 * offset + 1], backward: vec![0; 2 * offset + 1], offset: offset as isize }
+    }
+}
+
+// Changes of a block of deletions and insertions, the n-th deleted
+// paragraph paired with the n-th inserted one when they are similar.
+fn pair_changes(deleted: &mut Vec<&String>, inserted: &mut Vec<&String>, changes: &mut Vec<Change>) {
+    let paired = cmp::min(deleted.len(), inserted.len());
+    let mut unpaired = Vec::new();
+
+    for (old, new) in deleted.iter().zip(inserted.iter()) {
+        if similarity(old, new) >= MIN_SIMILARITY {
+            let (old, new) = word_diff(old, new);
+            changes.push(Change::Changed(old, new));
+        } else {
+            changes.push(Change::Deleted((*old).clone()));
+            unpaired.push(Change::Inserted((*new).clone()));
+        }
+    }
+    changes.extend(deleted[paired..].iter().map(|old| Change::Deleted((*old).clone())));
+    changes.extend(unpaired);
+    changes.extend(inserted[paired..].iter().map(|new| Change::Inserted((*new).clone())));
+    deleted.clear();
+    inserted.clear();
+}
+
+fn similarity(old: &str, new: &str) -> f64 {
+    let old_words: Vec<&str> = old.split_whitespace().collect();
+    let new_words: Vec<&str> = new.split_whitespace().collect();
+    let common = common_subsequence(&old_words, &new_words).iter()
+        .filter(|op| matches!(op, Op::Equal(..)))
+        .count();
+    2.0 * common as f64 / (old_words.len() + new_words.len()) as f64
+}
+
+// Ranges of the changes to show, each block of changes with its context,
+// the blocks closer than twice the context merged.
+fn hunks(changes: &[Change], context: usize) -> Vec<(usize, usize)> {
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (i, change) in changes.iter().enumerate() {
+        if let Change::Equal(_) = *change {
+            continue;
+        }
+        let start = i.saturating_sub(context);
+        let end = cmp::min(i + 1 + context, changes.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end))
+        }
+    }
+    hunks
+}
+
+fn range(start: usize, count: usize) -> String {
+    match count {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, count)
+    }
+}
+
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut size = 0;
+
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        if size > 0 && size + 1 + word.len() > width {
+            lines.push(line.split_off(0));
+            size = 0;
+        }
+        while word.len() > width {
+            let rest = word.split_off(width);
+            lines.push(word.into_iter().collect());
+            word = rest;
+        }
+        if size > 0 {
+            line.push(' ');
+            size += 1;
+        }
+        size += word.len();
+        line.extend(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+
+// Text of words separated by spaces, the successive marked ones enclosed
+// in a single pair of marks.
+struct Marked {
+    text: String,
+    open: &'static str,
+    close: &'static str,
+    in_mark: bool
+}
+
+impl Marked {
+    fn new(open: &'static str, close: &'static str) -> Marked {
+        Marked { text: String::new(), open, close, in_mark: false }
+    }
+
+    fn push(&mut self, word: &str, marked: bool) {
+        if self.in_mark && !marked {
+            self.text.push_str(self.close);
+            self.in_mark = false;
+        }
+        if !self.text.is_empty() {
+            self.text.push(' ');
+        }
+        if marked && !self.in_mark {
+            self.text.push_str(self.open);
+            self.in_mark = true;
+        }
+        self.text.push_str(word);
+    }
+
+    fn finish(mut self) -> String {
+        if self.in_mark {
+            self.text.push_str(self.close);
+        }
+        self.text
+    }
+}
@@ -0,0 +1,182 @@
+// Document
+// In-memory tree of a document, built from the reader events, for the
+// tasks needing to look around a block rather than to follow the events:
+//   sections
+//     - paragraph, with its heading level if it is one
+//     - table
+//       - row
+//         - cell
+//   notes
+extern crate serde;
+
+use std::mem;
+use std::path::Path;
+
+use self::serde::Serialize;
+
+use mso_doc;
+use mso_doc::{MsoError, NoteKind, Role};
+
+
+#[derive(Debug, Default, Serialize)]
+pub struct Document {
+    pub sections: Vec<Section>,
+    pub notes: Vec<Note>
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Section {
+    pub blocks: Vec<Block>
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Block {
+    Paragraph {
+        style: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        level: Option<u32>,
+        // role of the style map, other than a heading
+        #[serde(skip_serializing_if = "Option::is_none")]
+        role: Option<Role>,
+        // the readers join the runs of a paragraph into its whole text
+        text: String
+    },
+    Table {
+        rows: Vec<Row>
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Row {
+    pub header: bool,
+    pub cells: Vec<Cell>
+}
+
+// A cell has as many entries as it has paragraphs.
+#[derive(Debug, Serialize)]
+pub struct Cell {
+    pub style: String,
+    pub text: String
+}
+
+#[derive(Debug, Serialize)]
+pub struct Note {
+    pub kind: NoteKind,
+    pub id: String,
+    pub text: String
+}
+
+impl Document {
+    pub fn parse<P: AsRef<Path>>(filepath: P, options: &mso_doc::Options) -> Result<Document, MsoError> {
+        let mut builder = DocumentBuilder::default();
+        mso_doc::parse(filepath, options, &mut builder)?;
+        Ok(builder.finish())
+    }
+
+    // Blocks of all the sections, in document order.
+    pub fn blocks(&self) -> impl Iterator<Item=&Block> {
+        self.sections.iter().flat_map(|section| section.blocks.iter())
+    }
+
+    pub fn paragraphs(&self) -> impl Iterator<Item=&Block> {
+        self.blocks().filter(|block| block.is_paragraph())
+    }
+
+    pub fn tables(&self) -> impl Iterator<Item=&Block> {
+        self.blocks().filter(|block| !block.is_paragraph())
+    }
+}
+
+impl Block {
+    pub fn is_paragraph(&self) -> bool {
+        matches!(*self, Block::Paragraph { .. })
+    }
+
+    // Text of a paragraph, or of the cells of a table row by row.
+    pub fn text(&self) -> String {
+        match *self {
+            Block::Paragraph { ref text, .. } => text.clone(),
+            Block::Table { ref rows } => rows.iter()
+                .map(|row| row.cells.iter().map(|cell| cell.text.as_str()).collect::<Vec<_>>().join("\t"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    }
+}
+
+
+// Reader building the document from the events, usable with any of the
+// mso_doc parse functions.
+#[derive(Default)]
+pub struct DocumentBuilder {
+    document: Document,
+    section: Section,
+    table: Option<Vec<Row>>
+}
+
+impl DocumentBuilder {
+    pub fn finish(mut self) -> Document {
+        self.close_table();
+        self.document.sections.push(self.section);
+        self.document
+    }
+
+    fn close_table(&mut self) {
+        if let Some(rows) = self.table.take() {
+            self.section.blocks.push(Block::Table { rows });
+        }
+    }
+
+    fn push_paragraph(&mut self, text: &str, style: &str, level: Option<u32>, role: Option<Role>) {
+        self.close_table();
+        self.section.blocks.push(Block::Paragraph {
+            style: style.to_owned(),
+            level,
+            role,
+            text: text.to_owned()
+        });
+    }
+}
+
+impl mso_doc::WordReader for DocumentBuilder {
+    fn paragraph_row(&mut self, text: &str, style: &str) {
+        self.push_paragraph(text, style, None, None);
+    }
+
+    fn heading_row(&mut self, text: &str, style: &str, level: u32) {
+        self.push_paragraph(text, style, Some(level), None);
+    }
+
+    fn role_row(&mut self, text: &str, style: &str, role: Role) {
+        self.push_paragraph(text, style, None, Some(role));
+    }
+
+    fn table_new_row(&mut self) {
+        self.table.get_or_insert_with(Vec::new).push(Row::default());
+    }
+
+    fn table_closed(&mut self) {
+        self.close_table();
+    }
+
+    fn table_cell(&mut self, text: &str, style: &str, header: bool) {
+        let rows = self.table.get_or_insert_with(Vec::new);
+        if rows.is_empty() {
+            rows.push(Row::default());
+        }
+        let row = rows.last_mut().unwrap();
+        row.header |= header;
+        row.cells.push(Cell { style: style.to_owned(), text: text.to_owned() });
+    }
+
+    fn section_break(&mut self) {
+        self.close_table();
+        let section = mem::take(&mut self.section);
+        self.document.sections.push(section);
+    }
+
+    fn note(&mut self, kind: NoteKind, id: &str, text: &str) {
+        self.document.notes.push(Note { kind, id: id.to_owned(), text: text.to_owned() });
+    }
+}
@@ -0,0 +1,114 @@
+// Search of documents as grep searches files, each paragraph or table
+// cell being a line, numbered from 1 in the order of the document:
+//   contract.docx:12:Normal:The buyer pays within 30 days
+extern crate regex;
+
+use std::io;
+use std::io::Write;
+use std::process;
+
+use self::regex::{Regex, RegexBuilder};
+
+use mso_to_text::mso_doc;
+use batch;
+use parameters::GrepOptions;
+
+
+struct Match {
+    index: usize,
+    style: String,
+    text: String
+}
+
+// Reader keeping the paragraphs and cells matching the pattern.
+struct Matcher<'a> {
+    regex: &'a Regex,
+    index: usize,
+    matches: Vec<Match>
+}
+
+impl<'a> Matcher<'a> {
+    fn line(&mut self, text: &str, style: &str) {
+        self.index += 1;
+        if self.regex.is_match(text) {
+            self.matches.push(Match { index: self.index, style: style.to_owned(), text: text.replace('\n', " ") });
+        }
+    }
+}
+
+impl<'a> mso_doc::WordReader for Matcher<'a> {
+    fn paragraph_row(&mut self, text: &str, style: &str) {
+        self.line(text, style);
+    }
+
+    fn table_new_row(&mut self) {}
+
+    fn table_closed(&mut self) {}
+
+    fn table_cell(&mut self, text: &str, style: &str, _header: bool) {
+        self.line(text, style);
+    }
+}
+
+fn search(input: &batch::Input, regex: &Regex, options: &mso_doc::Options) -> Result<Vec<Match>, mso_doc::MsoError> {
+    let mut matcher = Matcher { regex, index: 0, matches: Vec::new() };
+    mso_doc::parse(&input.path, options, &mut matcher)?;
+    Ok(matcher.matches)
+}
+
+// Exits as grep does: 0 if any line matched, 1 if none, 2 on any error.
+pub fn run(params: GrepOptions) {
+    let regex = RegexBuilder::new(&params.pattern)
+        .case_insensitive(params.ignore_case)
+        .build()
+        .unwrap_or_else(|err| {
+            eprintln!("bad regular expression: {}", err);
+            process::exit(2);
+        });
+    let selection = batch::Selection::new(&params.include, &params.exclude)
+        .unwrap_or_else(|err| {
+            eprintln!("bad pattern: {}", err);
+            process::exit(2);
+        });
+    let options = mso_doc::Options {
+        password: params.password.clone(),
+        limits: params.limits.clone(),
+        ..mso_doc::Options::default()
+    };
+
+    let (inputs, unreadable) = selection.collect(&params.files);
+    for (path, err) in &unreadable {
+        eprintln!("{}: {}", path.display(), err);
+    }
+
+    let stdout = io::stdout();
+    let mut output = io::BufWriter::new(stdout.lock());
+    let mut failed = !unreadable.is_empty();
+    let mut matched = false;
+    batch::run(&inputs, params.jobs,
+        |_, input| search(input, &regex, &options),
+        |input, result| {
+            let matches = match result {
+                Ok(matches) => matches,
+                Err(err) => {
+                    eprintln!("{}: {}", input.path.display(), err);
+                    failed = true;
+                    return;
+                }
+            };
+            matched |= !matches.is_empty();
+
+            let path = input.path.display();
+            let written = if params.files_with_matches {
+                if matches.is_empty() { Ok(()) } else { writeln!(output, "{}", path) }
+            } else if params.count {
+                writeln!(output, "{}:{}", path, matches.len())
+            } else {
+                matches.iter().try_for_each(|m| writeln!(output, "{}:{}:{}:{}", path, m.index, m.style, m.text))
+            };
+            ::check_output(written);
+        });
+    ::check_output(output.flush());
+
+    process::exit(if failed { 2 } else if matched { 0 } else { 1 });
+}
@@ -0,0 +1,8 @@
+pub mod mso_doc;
+pub mod output;
+pub mod converters;
+pub mod document;
+pub mod outline;
+pub mod chunks;
+pub mod stats;
+pub mod diff;
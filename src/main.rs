@@ -1,57 +1,273 @@
 
+extern crate mso_to_text;
+extern crate serde_json;
+
 mod parameters;
-mod mso_doc;
+mod batch;
+mod grep;
+
+use std::io;
+use std::io::Write;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+use mso_to_text::mso_doc;
+use mso_to_text::output::Encoder;
+use mso_to_text::converters::{TextConverter, CsvConverter};
+use mso_to_text::document::Document;
+use mso_to_text::outline;
+use mso_to_text::chunks;
+use mso_to_text::stats;
+use mso_to_text::diff;
+use parameters::{ArgOptions, Command, DiffOptions, OutputFormat};
 
 
-struct Converter {
-    started_table_row: bool,
-    column_i: u32
+// Documents to route elsewhere get their own exit status.
+fn exit_code(err: &mso_doc::MsoError) -> i32 {
+    match *err {
+        mso_doc::MsoError::LegacyFormat(_) => 3,
+        mso_doc::MsoError::Encrypted | mso_doc::MsoError::WrongPassword => 4,
+        mso_doc::MsoError::LimitExceeded(_) => 5,
+        _ => 1
+    }
 }
 
-impl Default for Converter {
-    fn default() -> Converter {
-        Converter {
-            started_table_row: false,
-            column_i: 0
+
+fn convert<W: Write>(path: &Path, params: &ArgOptions, options: &mso_doc::Options, mut out: W)
+    -> Result<W, mso_doc::MsoError> {
+        if params.stats {
+            let mut counter = stats::StatsCounter::default();
+            mso_doc::parse(path, options, &mut counter)?;
+            let report = stats::Report {
+                counted: counter.finish(),
+                saved: mso_doc::saved_counts(fs::File::open(path)?, options)?
+            };
+            match params.format {
+                OutputFormat::Json => {
+                    serde_json::to_writer_pretty(&mut out, &report).map_err(io::Error::from)?;
+                    writeln!(out)?;
+                },
+                _ => stats::write_text(&report, &mut out)?
+            }
+            out.flush()?;
+            return Ok(out);
+        }
+
+        if params.outline {
+            let headings = outline::headings(&Document::parse(path, options)?);
+            match params.format {
+                OutputFormat::Json => {
+                    serde_json::to_writer_pretty(&mut out, &headings).map_err(io::Error::from)?;
+                    writeln!(out)?;
+                },
+                _ => outline::write_text(&headings, &mut out)?
+            }
+            out.flush()?;
+            return Ok(out);
+        }
+
+        match params.format {
+            OutputFormat::Text => {
+                let mut converter = TextConverter::new(out);
+                mso_doc::parse(path, options, &mut converter)?;
+                Ok(converter.finish()?)
+            },
+            OutputFormat::Csv => {
+                let mut converter = CsvConverter::new(out);
+                mso_doc::parse(path, options, &mut converter)?;
+                Ok(converter.finish()?)
+            },
+            OutputFormat::Json => {
+                let document = Document::parse(path, options)?;
+                serde_json::to_writer_pretty(&mut out, &document).map_err(io::Error::from)?;
+                writeln!(out)?;
+                out.flush()?;
+                Ok(out)
+            },
+            OutputFormat::Chunks => {
+                let document = Document::parse(path, options)?;
+                let chunks = chunks::chunks(&document, params.chunk_size);
+                chunks::write_json_lines(&chunks, &path.to_string_lossy(), &mut out)?;
+                out.flush()?;
+                Ok(out)
+            }
         }
     }
-}
 
-impl mso_doc::WordReader for Converter {
-    fn paragraph_row(&mut self, text: &String, style: &String) {
-        println!("{}\t{}", style, text);
+// Outcome of a conversion, with what it writes on the shared output.
+// Converts the document to its mirrored path in the output directory, or
+// to its slot of the shared output.
+fn convert_input<W: Write>(input: &batch::Input, index: usize, params: &ArgOptions, options: &mso_doc::Options,
+                           output: &batch::OrderedOutput<W>) -> Result<(), mso_doc::MsoError> {
+    match params.output_dir {
+        Some(ref output_dir) => {
+            let output_path = input.output_path(Path::new(output_dir), params.format.extension());
+            let result = output_path.parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::File::create(&output_path))
+                .map_err(mso_doc::MsoError::from)
+                .and_then(|file| {
+                    let file = Encoder::new(io::BufWriter::new(file), params.newline, params.bom);
+                    convert(&input.path, params, options, file)
+                })
+                .map(|_| ());
+            if result.is_err() {
+                // no partial conversion left behind
+                let _ = fs::remove_file(&output_path);
+            }
+            result
+        },
+        None => {
+            let mut slot = output.slot(index, format!("Input: {}", input.path.display()));
+            let result = convert(&input.path, params, options, &mut slot).map(|_| ());
+            slot.finish().map_err(mso_doc::MsoError::from).and(result)
+        }
     }
+}
+
+// Destination of the documents not written in an output directory.
+fn shared_output(params: &ArgOptions) -> io::Result<Encoder<Box<dyn Write + Send>>> {
+    let out: Box<dyn Write + Send> = match params.output {
+        Some(ref path) => Box::new(io::BufWriter::new(fs::File::create(path)?)),
+        None => Box::new(io::stdout())
+    };
+    Ok(Encoder::new(out, params.newline, params.bom))
+}
+
+// Style map of a JSON file, or a TOML one after its extension.
+fn read_style_map(path: &str) -> mso_doc::StyleMap {
+    let style_map = fs::read_to_string(path).map_err(|err| err.to_string()).and_then(|content| {
+        if path.to_lowercase().ends_with(".toml") {
+            mso_doc::StyleMap::from_toml(&content)
+        } else {
+            mso_doc::StyleMap::from_json(&content)
+        }
+    });
+    style_map.unwrap_or_else(|err| {
+        eprintln!("bad style map {}: {}", path, err);
+        process::exit(2);
+    })
+}
 
-    fn table_new_row(&mut self) {
-        if self.started_table_row {
-            println!("");
+// Failing to write on the shared output stops the conversions, quietly
+// when its reader is gone as `head` does.
+fn check_output(result: io::Result<()>) {
+    if let Err(err) = result {
+        if err.kind() == io::ErrorKind::BrokenPipe {
+            process::exit(0);
         }
-        self.column_i = 0;
-        self.started_table_row = false;
+        eprintln!("output: {}", err);
+        process::exit(1);
     }
+}
+
+
+fn main() {
+    match parameters::parse() {
+        Command::Convert(params) => convert_all(params),
+        Command::Grep(params) => grep::run(params),
+        Command::Diff(params) => diff_documents(params)
+    }
+}
 
-    fn table_closed(&mut self) {
-        if self.started_table_row {
-            println!("");
+fn convert_all(params: ArgOptions) {
+    let options = mso_doc::Options {
+        password: params.password.clone(),
+        debug: params.debug,
+        limits: params.limits.clone(),
+        style_filter: mso_doc::StyleFilter::new(&params.include_style, &params.exclude_style)
+            .unwrap_or_else(|err| {
+                eprintln!("bad style pattern: {}", err);
+                process::exit(2);
+            }),
+        style_map: params.style_map.as_ref().map(|path| read_style_map(path)).unwrap_or_default()
+    };
+    let selection = batch::Selection::new(&params.include, &params.exclude)
+        .unwrap_or_else(|err| {
+            eprintln!("bad pattern: {}", err);
+            process::exit(2);
+        });
+
+    let (inputs, unreadable) = selection.collect(&params.files);
+    for (path, err) in &unreadable {
+        eprintln!("{}: {}", path.display(), err);
+    }
+    if let Some(ref output_dir) = params.output_dir {
+        if let Some((first, second, path)) = batch::same_output(&inputs, Path::new(output_dir), params.format.extension()) {
+            eprintln!("{} and {} would both be converted to {}",
+                      first.path.display(), second.path.display(), path.display());
+            process::exit(2);
         }
     }
 
-    fn table_cell(&mut self, text: &String, style: &String, header: bool) {
-        self.started_table_row = true;
-        self.column_i += 1;
-        print!("|{}{} {}\t{}",
-               if header { "#" } else { "" },
-               self.column_i,
-               style,
-               text);
+    let output = shared_output(&params).unwrap_or_else(|err| {
+        eprintln!("{}: {}", params.output.as_ref().map_or("", |path| path.as_str()), err);
+        process::exit(1);
+    });
+    let output = batch::OrderedOutput::new(output);
+
+    let mut failed = unreadable.len();
+    let mut last_code = 1;
+    batch::run(&inputs, params.jobs,
+        |index, input| convert_input(input, index, &params, &options, &output),
+        |input, result| {
+            if let Some(err) = output.error() {
+                check_output(Err(err));
+            }
+            if let Err(err) = result {
+                eprintln!("{}: {}", input.path.display(), err);
+                failed += 1;
+                last_code = exit_code(&err);
+            }
+        });
+
+    check_output(output.finish());
+
+    let batch = params.files.len() > 1 || params.files.iter().any(|file| Path::new(file).is_dir());
+    if batch {
+        eprintln!("{} converted, {} failed", inputs.len() + unreadable.len() - failed, failed);
+    }
+    if failed > 0 {
+        // a single document keeps the status telling why it failed
+        process::exit(if batch { 1 } else { last_code });
     }
 }
 
 
-fn main() {
-    let params = parameters::parse();
-    println!("Input: {}", params.filename);
-    // TODO deal with all errors here: file doesn't exist, bad file format, bad xml structure with
-    // making your head burn with Rust error handling
-    mso_doc::parse(&params.filename, &mut Converter::default());
+// Exits as diff does: 0 if the documents have the same text, 1 if not,
+// 2 if a conversion failed, unless it has its own status.
+fn diff_documents(params: DiffOptions) {
+    let options = mso_doc::Options {
+        password: params.password.clone(),
+        limits: params.limits.clone(),
+        ..mso_doc::Options::default()
+    };
+    let paragraphs = |path: &str| {
+        let mut paragraphs = diff::Paragraphs::default();
+        match mso_doc::parse(Path::new(path), &options, &mut paragraphs) {
+            Ok(()) => paragraphs.finish(),
+            Err(err) => {
+                eprintln!("{}: {}", path, err);
+                process::exit(match exit_code(&err) {
+                    1 => 2,
+                    code => code
+                });
+            }
+        }
+    };
+    let changes = diff::diff(&paragraphs(&params.old), &paragraphs(&params.new));
+    let same = changes.iter().all(|change| matches!(*change, diff::Change::Equal(_)));
+
+    if !same {
+        let stdout = io::stdout();
+        let mut output = io::BufWriter::new(stdout.lock());
+        check_output(if params.side_by_side {
+            diff::write_side_by_side(&changes, params.width, &mut output)
+        } else {
+            diff::write_unified(&changes, &params.old, &params.new, params.context, &mut output)
+        });
+        check_output(output.flush());
+    }
+    process::exit(if same { 0 } else { 1 });
 }
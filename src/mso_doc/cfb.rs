@@ -0,0 +1,254 @@
+// Compound File Binary
+// The OLE container of the legacy binary formats (doc, xls, ppt), and of
+// encrypted OOXML documents:
+//   header        512 bytes, with the first 109 FAT sector numbers
+//   sectors       of 512 or 4096 bytes, chained by the FAT
+//   directory     entries of 128 bytes, storages and streams, as a tree
+use std::io;
+
+use mso_doc::*;
+
+
+pub const MAGIC: &[u8] = b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1";
+
+const HEADER_SIZE: usize = 512;
+const DIR_ENTRY_SIZE: usize = 128;
+const HEADER_DIFAT_COUNT: usize = 109;
+const MINI_SECTOR_SIZE: usize = 64;
+
+// special sector numbers
+const END_OF_CHAIN: u32 = 0xFFFF_FFFE;
+const NO_STREAM: u32 = 0xFFFF_FFFF;
+const MAX_SECTOR: u32 = 0xFFFF_FFFA;
+
+const ENTRY_ROOT: u8 = 5;
+
+
+pub fn le_u16(data: &[u8], offset: usize) -> u16 {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from(bytes[0]) | (u16::from(bytes[1]) << 8))
+        .unwrap_or(0)
+}
+
+pub fn le_u32(data: &[u8], offset: usize) -> u32 {
+    data.get(offset..offset + 4)
+        .map(|bytes| bytes.iter().rev().fold(0, |value, &byte| (value << 8) | u32::from(byte)))
+        .unwrap_or(0)
+}
+
+
+// Entry
+// Name, type and tree links of a storage or stream, and the first sector
+// and size of a stream.
+pub struct Entry {
+    pub name: String,
+    entry_type: u8,
+    left: u32,
+    right: u32,
+    child: u32,
+    start: u32,
+    size: usize
+}
+
+pub struct CompoundFile {
+    data: Vec<u8>,
+    sector_size: usize,
+    fat: Vec<u32>,
+    entries: Vec<Entry>
+}
+
+impl CompoundFile {
+    pub fn open<R: io::Read>(mut source: R) -> Result<CompoundFile, MsoError> {
+        let mut data = Vec::new();
+        source.read_to_end(&mut data)?;
+
+        if data.len() < HEADER_SIZE || !data.starts_with(MAGIC) {
+            return Err(MsoError::BadCompoundFile("truncated header".to_owned()));
+        }
+
+        let sector_size = match le_u16(&data, 0x1E) {
+            9 => 512,
+            12 => 4096,
+            shift => return Err(MsoError::BadCompoundFile(format!("sector shift {}", shift)))
+        };
+
+        let mut file = CompoundFile {
+            data,
+            sector_size,
+            fat: Vec::new(),
+            entries: Vec::new()
+        };
+        file.read_fat()?;
+        file.read_directory()?;
+        Ok(file)
+    }
+
+    fn sector(&self, sector: u32) -> Result<&[u8], MsoError> {
+        let start = (sector as usize + 1) * self.sector_size;
+        self.data.get(start..start + self.sector_size)
+            .ok_or_else(|| MsoError::BadCompoundFile(format!("sector {} out of file", sector)))
+    }
+
+    fn sector_count(&self) -> usize {
+        self.data.len() / self.sector_size
+    }
+
+    // FAT sectors are listed by the header, then by the DIFAT sectors,
+    // each ending with the next DIFAT sector number.
+    fn read_fat(&mut self) -> Result<(), MsoError> {
+        let fat_count = le_u32(&self.data, 0x2C) as usize;
+        let mut fat_sectors: Vec<u32> = (0..HEADER_DIFAT_COUNT)
+            .map(|i| le_u32(&self.data, 0x4C + i * 4))
+            .collect();
+
+        let per_sector = self.sector_size / 4 - 1;
+        let mut difat = le_u32(&self.data, 0x44);
+        let mut visited = 0;
+        while difat <= MAX_SECTOR && fat_sectors.len() < fat_count {
+            visited += 1;
+            if visited > self.sector_count() {
+                return Err(MsoError::BadCompoundFile("loop in DIFAT".to_owned()));
+            }
+            let sector = self.sector(difat)?;
+            fat_sectors.extend((0..per_sector).map(|i| le_u32(sector, i * 4)));
+            difat = le_u32(sector, per_sector * 4);
+        }
+        fat_sectors.truncate(fat_count);
+
+        let mut fat = Vec::with_capacity(fat_sectors.len() * self.sector_size / 4);
+        for &fat_sector in &fat_sectors {
+            let sector = self.sector(fat_sector)?;
+            fat.extend((0..self.sector_size / 4).map(|i| le_u32(sector, i * 4)));
+        }
+        self.fat = fat;
+        Ok(())
+    }
+
+    // Sector numbers of the chain starting at the given sector.
+    fn chain(&self, start: u32) -> Result<Vec<u32>, MsoError> {
+        let mut chain = Vec::new();
+        let mut sector = start;
+        while sector != END_OF_CHAIN {
+            if sector > MAX_SECTOR || chain.len() > self.fat.len() {
+                return Err(MsoError::BadCompoundFile("broken sector chain".to_owned()));
+            }
+            chain.push(sector);
+            sector = *self.fat.get(sector as usize)
+                .ok_or_else(|| MsoError::BadCompoundFile(format!("sector {} out of FAT", sector)))?;
+        }
+        Ok(chain)
+    }
+
+    fn read_directory(&mut self) -> Result<(), MsoError> {
+        let mut entries = Vec::new();
+
+        for sector in self.chain(le_u32(&self.data, 0x30))? {
+            let sector = self.sector(sector)?;
+            for raw in sector.chunks(DIR_ENTRY_SIZE) {
+                let name_length = (le_u16(raw, 64) as usize).min(64);
+                let name: Vec<u16> = (0..name_length / 2)
+                    .map(|i| le_u16(raw, i * 2))
+                    .take_while(|&c| c != 0)
+                    .collect();
+                entries.push(Entry {
+                    name: String::from_utf16_lossy(&name),
+                    entry_type: raw[66],
+                    left: le_u32(raw, 68),
+                    right: le_u32(raw, 72),
+                    child: le_u32(raw, 76),
+                    start: le_u32(raw, 116),
+                    // the high part of the size is not reliable in version 3 files
+                    size: le_u32(raw, 120) as usize
+                });
+            }
+        }
+
+        match entries.first() {
+            Some(root) if root.entry_type == ENTRY_ROOT => (),
+            _ => return Err(MsoError::BadCompoundFile("missing root entry".to_owned()))
+        }
+        self.entries = entries;
+        Ok(())
+    }
+
+    // Entries directly under the root storage, walking the siblings tree.
+    pub fn root_entries(&self) -> Vec<&Entry> {
+        let mut found = Vec::new();
+        let mut pending = vec![self.entries[0].child];
+        while let Some(id) = pending.pop() {
+            if id == NO_STREAM || found.len() >= self.entries.len() {
+                continue;
+            }
+            if let Some(entry) = self.entries.get(id as usize) {
+                found.push(entry);
+                pending.push(entry.left);
+                pending.push(entry.right);
+            }
+        }
+        found
+    }
+
+    fn find(&self, name: &str) -> Option<&Entry> {
+        self.root_entries().into_iter()
+            .find(|entry| entry.name.to_lowercase() == name.to_lowercase())
+    }
+
+    pub fn has_entry(&self, name: &str) -> bool {
+        self.find(name).is_some()
+    }
+
+    // Content of a stream under the root storage, small streams being
+    // stored in mini sectors of 64 bytes inside the root entry stream.
+    pub fn stream(&self, name: &str) -> Result<Vec<u8>, MsoError> {
+        let entry = self.find(name)
+            .ok_or_else(|| MsoError::MissingPart(name.to_owned()))?;
+        if entry.size > self.data.len() {
+            return Err(MsoError::BadCompoundFile(format!("stream {} larger than the file", name)));
+        }
+
+        if entry.size < le_u32(&self.data, 0x38) as usize {
+            self.mini_stream(entry)
+        } else {
+            self.regular_stream(entry.start, entry.size)
+        }
+    }
+
+    fn regular_stream(&self, start: u32, size: usize) -> Result<Vec<u8>, MsoError> {
+        let mut content = Vec::with_capacity(size);
+        for sector in self.chain(start)? {
+            if content.len() >= size {
+                break;
+            }
+            content.extend_from_slice(self.sector(sector)?);
+        }
+        if content.len() < size {
+            return Err(MsoError::BadCompoundFile("truncated stream".to_owned()));
+        }
+        content.truncate(size);
+        Ok(content)
+    }
+
+    fn mini_stream(&self, entry: &Entry) -> Result<Vec<u8>, MsoError> {
+        let root = &self.entries[0];
+        let container = self.regular_stream(root.start, root.size)?;
+
+        let mut mini_fat = Vec::new();
+        for sector in self.chain(le_u32(&self.data, 0x3C))? {
+            let sector = self.sector(sector)?;
+            mini_fat.extend((0..self.sector_size / 4).map(|i| le_u32(sector, i * 4)));
+        }
+
+        let mut content = Vec::with_capacity(entry.size);
+        let mut sector = entry.start;
+        while content.len() < entry.size {
+            let start = sector as usize * MINI_SECTOR_SIZE;
+            let mini_sector = container.get(start..start + MINI_SECTOR_SIZE)
+                .ok_or_else(|| MsoError::BadCompoundFile(format!("mini sector {} out of stream", sector)))?;
+            content.extend_from_slice(mini_sector);
+            sector = *mini_fat.get(sector as usize)
+                .ok_or_else(|| MsoError::BadCompoundFile(format!("mini sector {} out of FAT", sector)))?;
+        }
+        content.truncate(entry.size);
+        Ok(content)
+    }
+}
@@ -0,0 +1,446 @@
+// Word 97-2003 binary document
+// Streams of the compound file:
+//   WordDocument   FIB (file information block), text, FKP pages of
+//                  paragraph properties
+//   0Table/1Table  piece table (CLX), style sheet (STSH), section table
+//                  (PlcfSed), and the table of FKP pages (PlcBtePapx),
+//                  located by the FIB
+// The main text is the first ccpText characters; paragraphs end with 0x0D,
+// table cells and rows with 0x07, and sections with 0x0C, which is a page
+// break elsewhere.
+extern crate encoding_rs;
+
+use std::char;
+
+use mso_doc::*;
+use mso_doc::cfb::{le_u16, le_u32};
+
+
+const WORD_IDENT: u16 = 0xA5EC;
+// first version of the format, Word 97
+const NFIB_WORD97: u16 = 0x00C1;
+
+const FKP_SIZE: usize = 512;
+
+// sprm of paragraph properties
+const SPRM_P_F_IN_TABLE: u16 = 0x2416;
+const SPRM_P_F_TTP: u16 = 0x2417;
+const SPRM_P_OUT_LVL: u16 = 0x2640;
+const SPRM_T_TABLE_HEADER: u16 = 0x3404;
+const SPRM_T_DEF_TABLE: u16 = 0xD608;
+
+// special characters
+const CELL_MARK: char = '\u{07}';
+const LINE_BREAK: char = '\u{0B}';
+const SECTION_MARK: char = '\u{0C}';
+const PARAGRAPH_MARK: char = '\r';
+const FIELD_BEGIN: char = '\u{13}';
+const FIELD_SEPARATOR: char = '\u{14}';
+const FIELD_END: char = '\u{15}';
+const NON_BREAKING_HYPHEN: char = '\u{1E}';
+
+
+pub fn parse(file: &cfb::CompoundFile, reader: &mut dyn WordReader) -> Result<(), MsoError> {
+    let word_document = file.stream("WordDocument")?;
+    let fib = Fib::parse(&word_document)?;
+    let table = file.stream(if fib.table_1 { "1Table" } else { "0Table" })?;
+
+    let pieces = Piece::parse_clx(fib.range(&table, fib.clx)?)?;
+    let styles = StyleSheet::parse(fib.range(&table, fib.stshf)?);
+    let properties = ParagraphRuns::parse(&word_document, fib.range(&table, fib.plcf_bte_papx)?);
+    let section_marks = section_marks(fib.range(&table, fib.plcf_sed)?);
+
+    let mut document = Document::default();
+    for piece in &pieces {
+        if piece.cp_start >= fib.ccp_text {
+            break;
+        }
+        let end = piece.cp_end.min(fib.ccp_text);
+        for (i, c) in piece.text(&word_document, end - piece.cp_start)?.into_iter().enumerate() {
+            let fc = piece.fc + i * if piece.compressed { 1 } else { 2 };
+            let section_end = c == SECTION_MARK && section_marks.binary_search(&(piece.cp_start + i)).is_ok();
+            document.read(c, fc, section_end, &properties, &styles, reader);
+        }
+    }
+    document.close(reader);
+    Ok(())
+}
+
+
+// Fib
+// Only the members locating the text and its properties.
+struct Fib {
+    table_1: bool,
+    ccp_text: usize,
+    // (fc, lcb) pairs in the table stream
+    stshf: (usize, usize),
+    plcf_sed: (usize, usize),
+    plcf_bte_papx: (usize, usize),
+    clx: (usize, usize)
+}
+
+impl Fib {
+    fn parse(data: &[u8]) -> Result<Fib, MsoError> {
+        if le_u16(data, 0) != WORD_IDENT {
+            return Err(MsoError::BadWordDocument("not a Word binary document".to_owned()));
+        }
+        if le_u16(data, 0x02) < NFIB_WORD97 {
+            return Err(MsoError::LegacyFormat("Word 6.0/95 document"));
+        }
+
+        let flags = le_u16(data, 0x0A);
+        if flags & 0x0100 != 0 {
+            return Err(MsoError::Encrypted);
+        }
+
+        let pair = |index: usize| {
+            let offset = 0x9A + index * 8;
+            (le_u32(data, offset) as usize, le_u32(data, offset + 4) as usize)
+        };
+
+        Ok(Fib {
+            table_1: flags & 0x0200 != 0,
+            ccp_text: le_u32(data, 0x4C) as usize,
+            stshf: pair(1),
+            plcf_sed: pair(6),
+            plcf_bte_papx: pair(13),
+            clx: pair(33)
+        })
+    }
+
+    fn range<'a>(&self, table: &'a [u8], (fc, lcb): (usize, usize)) -> Result<&'a [u8], MsoError> {
+        table.get(fc..fc.saturating_add(lcb))
+            .ok_or_else(|| MsoError::BadWordDocument("structure out of the table stream".to_owned()))
+    }
+}
+
+
+// Piece
+// Range of characters stored contiguously in the WordDocument stream,
+// either as UTF-16 or as compressed 8-bit characters.
+struct Piece {
+    cp_start: usize,
+    cp_end: usize,
+    fc: usize,
+    compressed: bool
+}
+
+impl Piece {
+    // Clx: Prc (0x01, cb, grpprl) repeated, then Pcdt (0x02, lcb, PlcPcd)
+    fn parse_clx(clx: &[u8]) -> Result<Vec<Piece>, MsoError> {
+        let mut offset = 0;
+        while clx.get(offset) == Some(&0x01) {
+            offset += 3 + le_u16(clx, offset + 1) as usize;
+        }
+        if clx.get(offset) != Some(&0x02) {
+            return Err(MsoError::BadWordDocument("missing piece table".to_owned()));
+        }
+
+        let lcb = le_u32(clx, offset + 1) as usize;
+        let plc = clx.get(offset + 5..(offset + 5).saturating_add(lcb))
+            .ok_or_else(|| MsoError::BadWordDocument("truncated piece table".to_owned()))?;
+        let count = plc.len().saturating_sub(4) / 12;
+
+        // character positions only increase
+        let cps: Vec<usize> = (0..count + 1).map(|i| le_u32(plc, i * 4) as usize).collect();
+        if cps.windows(2).any(|pair| pair[1] < pair[0]) {
+            return Err(MsoError::BadWordDocument("pieces out of order".to_owned()));
+        }
+
+        Ok((0..count)
+            .map(|i| {
+                let fc = le_u32(plc, (count + 1) * 4 + i * 8 + 2);
+                let compressed = fc & 0x4000_0000 != 0;
+                let fc = (fc & 0x3FFF_FFFF) as usize;
+                Piece {
+                    cp_start: cps[i],
+                    cp_end: cps[i + 1],
+                    fc: if compressed { fc / 2 } else { fc },
+                    compressed
+                }
+            })
+            .collect())
+    }
+
+    fn text(&self, word_document: &[u8], length: usize) -> Result<Vec<char>, MsoError> {
+        let size = if self.compressed { length } else { length * 2 };
+        let bytes = word_document.get(self.fc..self.fc.saturating_add(size))
+            .ok_or_else(|| MsoError::BadWordDocument("text out of the document stream".to_owned()))?;
+
+        if self.compressed {
+            // one char per byte, as in windows-1252
+            let (text, _) = encoding_rs::WINDOWS_1252.decode_without_bom_handling(bytes);
+            Ok(text.chars().collect())
+        } else {
+            let units: Vec<u16> = (0..length).map(|i| le_u16(bytes, i * 2)).collect();
+            Ok(char::decode_utf16(units)
+                .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                .collect())
+        }
+    }
+}
+
+
+// PlcfSed: the character positions starting each section, and the end
+// of the last one, then the section descriptors. Sections other than the
+// last end with a section mark, just before the start of the next one.
+fn section_marks(plc: &[u8]) -> Vec<usize> {
+    let count = plc.len().saturating_sub(4) / 16;
+    let mut marks: Vec<usize> = (1..count + 1)
+        .map(|i| le_u32(plc, i * 4) as usize)
+        .filter(|&cp| cp > 0)
+        .map(|cp| cp - 1)
+        .collect();
+    marks.sort_unstable();
+    marks
+}
+
+
+// StyleSheet
+// STSH: Stshi, then for each style its size and STD (StdfBase, name as
+// an UTF-16 string with its length).
+#[derive(Default)]
+struct StyleSheet {
+    styles: Vec<Option<Style>>
+}
+
+struct Style {
+    name: String,
+    outline_level: Option<u32>
+}
+
+impl StyleSheet {
+    fn parse(stsh: &[u8]) -> StyleSheet {
+        let stshi_size = le_u16(stsh, 0) as usize;
+        let count = le_u16(stsh, 2) as usize;
+        let std_base_size = le_u16(stsh, 4) as usize;
+
+        let mut styles = Vec::with_capacity(count);
+        let mut offset = 2 + stshi_size;
+        for _ in 0..count {
+            let size = le_u16(stsh, offset) as usize;
+            let std = stsh.get(offset + 2..offset + 2 + size).unwrap_or(&[]);
+            offset += 2 + size;
+
+            if std.is_empty() {
+                styles.push(None);
+                continue;
+            }
+
+            // built-in headings are the styles 1 to 9
+            let sti = le_u16(std, 0) & 0x0FFF;
+            let length = le_u16(std, std_base_size) as usize;
+            let name: Vec<u16> = (0..length).map(|i| le_u16(std, std_base_size + 2 + i * 2)).collect();
+            let name = String::from_utf16_lossy(&name);
+            styles.push(Some(Style {
+                // aliases follow the name
+                name: name.split(',').next().unwrap_or("").to_owned(),
+                outline_level: if (1..=9).contains(&sti) { Some(u32::from(sti)) } else { None }
+            }));
+        }
+
+        StyleSheet { styles }
+    }
+
+    fn get(&self, istd: u16) -> Option<&Style> {
+        self.styles.get(istd as usize).and_then(|style| style.as_ref())
+    }
+}
+
+
+#[derive(Clone, Default)]
+struct ParagraphProperties {
+    istd: u16,
+    in_table: bool,
+    // row end mark, and whether its row is a header row
+    ttp: bool,
+    header: bool,
+    outline_level: Option<u32>
+}
+
+// ParagraphRuns
+// PlcBtePapx: file offsets, then the FKP page numbers for each range.
+// Each FKP page: crun at its end, crun + 1 file offsets, then for each run
+// the offset (in words) of its PapxInFkp: istd, then the sprm.
+#[derive(Default)]
+struct ParagraphRuns {
+    // (fc start, fc end, properties), sorted
+    runs: Vec<(usize, usize, ParagraphProperties)>
+}
+
+impl ParagraphRuns {
+    fn parse(word_document: &[u8], plc: &[u8]) -> ParagraphRuns {
+        let count = plc.len().saturating_sub(4) / 8;
+        let mut runs = Vec::new();
+
+        for i in 0..count {
+            let page_number = (le_u32(plc, (count + 1) * 4 + i * 4) & 0x003F_FFFF) as usize;
+            let page = match word_document.get(page_number * FKP_SIZE..(page_number + 1) * FKP_SIZE) {
+                Some(page) => page,
+                None => continue
+            };
+
+            let crun = page[FKP_SIZE - 1] as usize;
+            for run in 0..crun {
+                let fc_start = le_u32(page, run * 4) as usize;
+                let fc_end = le_u32(page, (run + 1) * 4) as usize;
+                let papx_offset = *page.get((crun + 1) * 4 + run * 13).unwrap_or(&0) as usize * 2;
+                runs.push((fc_start, fc_end, Self::parse_papx(page, papx_offset)));
+            }
+        }
+
+        runs.sort_by_key(|run| run.0);
+        ParagraphRuns { runs }
+    }
+
+    fn parse_papx(page: &[u8], offset: usize) -> ParagraphProperties {
+        if offset == 0 {
+            return ParagraphProperties::default();
+        }
+
+        let cb = *page.get(offset).unwrap_or(&0) as usize;
+        let (start, size) = if cb == 0 {
+            (offset + 2, *page.get(offset + 1).unwrap_or(&0) as usize * 2)
+        } else {
+            (offset + 1, cb * 2 - 1)
+        };
+        let papx = page.get(start..(start + size).min(page.len())).unwrap_or(&[]);
+
+        let mut properties = ParagraphProperties {
+            istd: le_u16(papx, 0),
+            ..ParagraphProperties::default()
+        };
+
+        let mut offset = 2;
+        while offset + 2 <= papx.len() {
+            let sprm = le_u16(papx, offset);
+            let operand = offset + 2;
+            let size = match sprm >> 13 {
+                0 | 1 => 1,
+                2 | 4 | 5 => 2,
+                3 => 4,
+                7 => 3,
+                _ if sprm == SPRM_T_DEF_TABLE => 1 + le_u16(papx, operand) as usize,
+                _ => 1 + *papx.get(operand).unwrap_or(&0) as usize
+            };
+
+            let value = *papx.get(operand).unwrap_or(&0);
+            match sprm {
+                SPRM_P_F_IN_TABLE => properties.in_table = value != 0,
+                SPRM_P_F_TTP => properties.ttp = value != 0,
+                SPRM_T_TABLE_HEADER => properties.header = value != 0,
+                SPRM_P_OUT_LVL if value < 9 => properties.outline_level = Some(u32::from(value) + 1),
+                _ => ()
+            }
+            offset = operand + size;
+        }
+
+        properties
+    }
+
+    fn get(&self, fc: usize) -> ParagraphProperties {
+        let i = match self.runs.binary_search_by_key(&fc, |run| run.0) {
+            Ok(i) => i,
+            Err(0) => return ParagraphProperties::default(),
+            Err(i) => i - 1
+        };
+        let (_, fc_end, ref properties) = self.runs[i];
+        if fc < fc_end { properties.clone() } else { ParagraphProperties::default() }
+    }
+}
+
+
+// Document
+// Cuts the text at paragraph marks, dropping field codes and objects. The
+// cells of a row are held until its end mark, which tells if it is a
+// header row.
+#[derive(Default)]
+struct Document {
+    text: String,
+    // for each open field, whether its result is reached
+    fields: Vec<bool>,
+    table_started: bool,
+    // text and style of the cells of the current row
+    row: Vec<(String, String)>
+}
+
+impl Document {
+    fn read(&mut self, c: char, fc: usize, section_end: bool, properties: &ParagraphRuns, styles: &StyleSheet,
+            reader: &mut dyn WordReader) {
+        match c {
+            FIELD_BEGIN => self.fields.push(false),
+            FIELD_SEPARATOR => {
+                if let Some(result) = self.fields.last_mut() {
+                    *result = true;
+                }
+            },
+            FIELD_END => { self.fields.pop(); },
+            // inside a field code
+            _ if self.fields.last() == Some(&false) => (),
+            PARAGRAPH_MARK | CELL_MARK => {
+                let properties = properties.get(fc);
+                self.end_paragraph(c, &properties, styles, reader);
+            },
+            SECTION_MARK if section_end => {
+                let properties = properties.get(fc);
+                self.end_paragraph(c, &properties, styles, reader);
+                reader.section_break();
+            },
+            LINE_BREAK => self.text.push('\n'),
+            NON_BREAKING_HYPHEN => self.text.push('\u{2011}'),
+            '\t' => self.text.push('\t'),
+            // pictures, notes references, optional hyphens
+            _ if c < ' ' => (),
+            _ => self.text.push(c)
+        }
+    }
+
+    fn end_paragraph(&mut self, mark: char, properties: &ParagraphProperties, styles: &StyleSheet, reader: &mut dyn WordReader) {
+        let style = styles.get(properties.istd);
+        let name = style.map(|style| style.name.as_str()).unwrap_or("");
+
+        if properties.in_table || mark == CELL_MARK {
+            if properties.ttp {
+                // row end mark, with the properties of the row
+                self.send_row(properties.header, reader);
+            } else {
+                self.row.push((self.text.clone(), name.to_owned()));
+            }
+        } else {
+            self.close_table(reader);
+
+            let outline_level = properties.outline_level
+                .or_else(|| style.and_then(|style| style.outline_level));
+            match outline_level {
+                Some(level) => reader.heading_row(&self.text, name, level),
+                None => reader.paragraph_row(&self.text, name)
+            }
+        }
+        self.text.clear();
+    }
+
+    fn send_row(&mut self, header: bool, reader: &mut dyn WordReader) {
+        if self.row.is_empty() {
+            return;
+        }
+        self.table_started = true;
+        reader.table_new_row();
+        for (text, style) in self.row.drain(..) {
+            reader.table_cell(&text, &style, header);
+            reader.table_cell_closed();
+        }
+    }
+
+    // Ends the table, its last row sent even without its end mark.
+    fn close_table(&mut self, reader: &mut dyn WordReader) {
+        self.send_row(false, reader);
+        if self.table_started {
+            reader.table_closed();
+            self.table_started = false;
+        }
+    }
+
+    fn close(&mut self, reader: &mut dyn WordReader) {
+        self.close_table(reader);
+    }
+}
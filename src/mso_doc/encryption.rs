@@ -0,0 +1,363 @@
+// Encrypted OOXML package (ECMA-376 agile and standard encryption)
+// Streams of the compound file:
+//   EncryptionInfo     version, then the key derivation parameters, as XML
+//                      (agile, 4.4) or binary header and verifier (standard, x.2)
+//   EncryptedPackage   size of the zip package, then the encrypted package
+extern crate aes;
+extern crate sha1;
+extern crate sha2;
+extern crate base64;
+
+use self::aes::cipher::{BlockDecrypt, KeyInit};
+use self::aes::cipher::generic_array::GenericArray;
+use self::base64::Engine;
+use self::sha2::Digest;
+
+use mso_doc::*;
+use mso_doc::cfb::{le_u16, le_u32};
+
+
+const SEGMENT_SIZE: usize = 4096;
+// Maximum spin count of the specification, a larger one being a denial of
+// service rather than a protection.
+const MAX_SPIN_COUNT: u32 = 10_000_000;
+
+// agile block keys
+const BLOCK_KEY_VERIFIER_INPUT: [u8; 8] = [0xfe, 0xa7, 0xd2, 0x76, 0x3b, 0x4b, 0x9e, 0x79];
+const BLOCK_KEY_VERIFIER_VALUE: [u8; 8] = [0xd7, 0xaa, 0x0f, 0x6d, 0x30, 0x61, 0x34, 0x4e];
+const BLOCK_KEY_KEY_VALUE: [u8; 8] = [0x14, 0x6e, 0x0b, 0xe7, 0xab, 0xac, 0xd0, 0xd6];
+
+// standard encryption
+const STANDARD_SPIN_COUNT: u32 = 50_000;
+const FLAG_AES: u32 = 0x20;
+
+
+// Decrypted zip package.
+pub fn decrypt(file: &cfb::CompoundFile, password: &str) -> Result<Vec<u8>, MsoError> {
+    let info = file.stream("EncryptionInfo")?;
+    let package = file.stream("EncryptedPackage")?;
+
+    let version = (le_u16(&info, 0), le_u16(&info, 2));
+    let key = match version {
+        (4, 4) => Agile::parse(&info)?.secret_key(password)?,
+        (2..=4, 2) => Standard::parse(&info)?.secret_key(password)?,
+        (major, minor) =>
+            return Err(MsoError::UnsupportedEncryption(format!("version {}.{}", major, minor)))
+    };
+
+    let size = le_u32(&package, 0) as usize | ((le_u32(&package, 4) as usize) << 16 << 16);
+    let encrypted = package.get(8..).unwrap_or(&[]);
+    if size > encrypted.len() {
+        return Err(MsoError::BadCompoundFile("truncated encrypted package".to_owned()));
+    }
+
+    let mut content = match key {
+        SecretKey::Agile(ref agile, ref key) => agile.decrypt_package(key, encrypted)?,
+        SecretKey::Standard(ref key) => {
+            let cipher = Cipher::new(key)?;
+            let mut content = encrypted[..encrypted.len() / 16 * 16].to_vec();
+            for block in content.chunks_mut(16) {
+                cipher.decrypt_block(block);
+            }
+            content
+        }
+    };
+    content.truncate(size);
+    Ok(content)
+}
+
+enum SecretKey {
+    Agile(Agile, Vec<u8>),
+    Standard(Vec<u8>)
+}
+
+
+#[derive(Clone, Copy)]
+enum Hash {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512
+}
+
+impl Hash {
+    fn parse(name: &str) -> Result<Hash, MsoError> {
+        match name {
+            "SHA1" | "SHA-1" => Ok(Hash::Sha1),
+            "SHA256" => Ok(Hash::Sha256),
+            "SHA384" => Ok(Hash::Sha384),
+            "SHA512" => Ok(Hash::Sha512),
+            _ => Err(MsoError::UnsupportedEncryption(format!("hash {}", name)))
+        }
+    }
+
+    fn digest(self, parts: &[&[u8]]) -> Vec<u8> {
+        fn digest<D: Digest>(parts: &[&[u8]]) -> Vec<u8> {
+            let mut hasher = D::new();
+            for part in parts {
+                hasher.update(part);
+            }
+            hasher.finalize().to_vec()
+        }
+
+        match self {
+            Hash::Sha1 => digest::<sha1::Sha1>(parts),
+            Hash::Sha256 => digest::<sha2::Sha256>(parts),
+            Hash::Sha384 => digest::<sha2::Sha384>(parts),
+            Hash::Sha512 => digest::<sha2::Sha512>(parts)
+        }
+    }
+
+    // H0 = H(salt + password), Hn = H(iterator + Hn-1)
+    fn spin(self, salt: &[u8], password: &str, spin_count: u32) -> Vec<u8> {
+        let password: Vec<u8> = password.encode_utf16()
+            .flat_map(|c| vec![c as u8, (c >> 8) as u8])
+            .collect();
+        let mut hash = self.digest(&[salt, &password]);
+        for i in 0..spin_count {
+            hash = self.digest(&[&i.to_le_bytes(), &hash]);
+        }
+        hash
+    }
+}
+
+enum Cipher {
+    Aes128(aes::Aes128),
+    Aes192(aes::Aes192),
+    Aes256(aes::Aes256)
+}
+
+impl Cipher {
+    fn new(key: &[u8]) -> Result<Cipher, MsoError> {
+        match key.len() {
+            16 => Ok(Cipher::Aes128(aes::Aes128::new(GenericArray::from_slice(key)))),
+            24 => Ok(Cipher::Aes192(aes::Aes192::new(GenericArray::from_slice(key)))),
+            32 => Ok(Cipher::Aes256(aes::Aes256::new(GenericArray::from_slice(key)))),
+            length => Err(MsoError::UnsupportedEncryption(format!("key of {} bits", length * 8)))
+        }
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let block = GenericArray::from_mut_slice(block);
+        match *self {
+            Cipher::Aes128(ref cipher) => cipher.decrypt_block(block),
+            Cipher::Aes192(ref cipher) => cipher.decrypt_block(block),
+            Cipher::Aes256(ref cipher) => cipher.decrypt_block(block)
+        }
+    }
+
+    // Decrypts in place the whole blocks, in CBC mode.
+    fn decrypt_cbc(&self, iv: &[u8], data: &mut [u8]) {
+        let mut previous = iv.to_vec();
+        let length = data.len() / 16 * 16;
+        for block in data[..length].chunks_mut(16) {
+            let encrypted = block.to_vec();
+            self.decrypt_block(block);
+            for (byte, iv_byte) in block.iter_mut().zip(&previous) {
+                *byte ^= iv_byte;
+            }
+            previous = encrypted;
+        }
+    }
+}
+
+// Truncated, or padded with 0x36, to the given size.
+fn fit(mut data: Vec<u8>, size: usize) -> Vec<u8> {
+    data.resize(size, 0x36);
+    data
+}
+
+
+// Agile
+// <encryption>
+//   <keyData saltValue="" hashAlgorithm="SHA512" keyBits="256" blockSize="16" cipherAlgorithm="AES"/>
+//   <keyEncryptors><keyEncryptor><p:encryptedKey spinCount="100000" saltValue=""
+//       encryptedVerifierHashInput="" encryptedVerifierHashValue="" encryptedKeyValue=""/>
+struct Agile {
+    key_data: KeyParameters,
+    encrypted_key: KeyParameters,
+    spin_count: u32,
+    verifier_hash_input: Vec<u8>,
+    verifier_hash_value: Vec<u8>,
+    key_value: Vec<u8>
+}
+
+struct KeyParameters {
+    salt: Vec<u8>,
+    hash: Hash,
+    key_bytes: usize,
+    block_size: usize
+}
+
+impl KeyParameters {
+    fn parse(attributes: &[OwnedAttribute]) -> Result<KeyParameters, MsoError> {
+        let cipher = attributes.value("cipherAlgorithm");
+        if cipher != "AES" {
+            return Err(MsoError::UnsupportedEncryption(format!("cipher {}", cipher)));
+        }
+        let chaining = attributes.value("cipherChaining");
+        if chaining != "ChainingModeCBC" {
+            return Err(MsoError::UnsupportedEncryption(format!("chaining {}", chaining)));
+        }
+
+        Ok(KeyParameters {
+            salt: base64_value(attributes, "saltValue")?,
+            hash: Hash::parse(&attributes.value("hashAlgorithm"))?,
+            key_bytes: attributes.value("keyBits").parse::<usize>().unwrap_or(0) / 8,
+            block_size: attributes.value("blockSize").parse().unwrap_or(16)
+        })
+    }
+}
+
+fn spin_count(attributes: &[OwnedAttribute]) -> Result<u32, MsoError> {
+    let value = attributes.value("spinCount");
+    match value.parse::<u64>() {
+        Ok(count) if count > MAX_SPIN_COUNT as u64 =>
+            Err(MsoError::UnsupportedEncryption(format!("spin count {} over {}", value, MAX_SPIN_COUNT))),
+        Ok(count) => Ok(count as u32),
+        Err(_) => Ok(0)
+    }
+}
+
+fn base64_value(attributes: &[OwnedAttribute], key: &str) -> Result<Vec<u8>, MsoError> {
+    base64::engine::general_purpose::STANDARD.decode(attributes.value(key))
+        .map_err(|_| MsoError::UnsupportedEncryption(format!("bad {}", key)))
+}
+
+impl Agile {
+    fn parse(info: &[u8]) -> Result<Agile, MsoError> {
+        let xml_parser = xml::reader::EventReader::new(info.get(8..).unwrap_or(&[]));
+        let mut key_data = None;
+        let mut agile = None;
+
+        for event in xml_parser {
+            if let XmlEvent::StartElement { ref name, ref attributes, .. } = event? {
+                if name.is_tag("keyData") {
+                    key_data = Some(KeyParameters::parse(attributes)?);
+                } else if name.is_tag("p:encryptedKey") && agile.is_none() {
+                    agile = Some((
+                        KeyParameters::parse(attributes)?,
+                        spin_count(attributes)?,
+                        base64_value(attributes, "encryptedVerifierHashInput")?,
+                        base64_value(attributes, "encryptedVerifierHashValue")?,
+                        base64_value(attributes, "encryptedKeyValue")?
+                    ));
+                }
+            }
+        }
+
+        match (key_data, agile) {
+            (Some(key_data), Some((encrypted_key, spin_count, input, value, key))) => Ok(Agile {
+                key_data,
+                encrypted_key,
+                spin_count,
+                verifier_hash_input: input,
+                verifier_hash_value: value,
+                key_value: key
+            }),
+            _ => Err(MsoError::UnsupportedEncryption("no password key encryptor".to_owned()))
+        }
+    }
+
+    fn decrypt_with(&self, spun: &[u8], block_key: &[u8], data: &[u8]) -> Result<Vec<u8>, MsoError> {
+        let parameters = &self.encrypted_key;
+        let key = fit(parameters.hash.digest(&[spun, block_key]), parameters.key_bytes);
+        let mut data = data.to_vec();
+        Cipher::new(&key)?.decrypt_cbc(&fit(parameters.salt.clone(), parameters.block_size), &mut data);
+        Ok(data)
+    }
+
+    fn secret_key(self, password: &str) -> Result<SecretKey, MsoError> {
+        let hash = self.encrypted_key.hash;
+        let spun = hash.spin(&self.encrypted_key.salt, password, self.spin_count);
+
+        let mut input = self.decrypt_with(&spun, &BLOCK_KEY_VERIFIER_INPUT, &self.verifier_hash_input)?;
+        input.truncate(self.encrypted_key.salt.len());
+        let expected = hash.digest(&[&input]);
+        let value = self.decrypt_with(&spun, &BLOCK_KEY_VERIFIER_VALUE, &self.verifier_hash_value)?;
+        if value.get(..expected.len()) != Some(&expected[..]) {
+            return Err(MsoError::WrongPassword);
+        }
+
+        let mut key = self.decrypt_with(&spun, &BLOCK_KEY_KEY_VALUE, &self.key_value)?;
+        key.truncate(self.key_data.key_bytes);
+        Ok(SecretKey::Agile(self, key))
+    }
+
+    // Segments of 4096 bytes, each with its own IV: H(salt + segment index).
+    fn decrypt_package(&self, key: &[u8], encrypted: &[u8]) -> Result<Vec<u8>, MsoError> {
+        let cipher = Cipher::new(key)?;
+        let mut content = encrypted.to_vec();
+        for (i, segment) in content.chunks_mut(SEGMENT_SIZE).enumerate() {
+            let iv = self.key_data.hash.digest(&[&self.key_data.salt, &(i as u32).to_le_bytes()]);
+            cipher.decrypt_cbc(&fit(iv, self.key_data.block_size), segment);
+        }
+        Ok(content)
+    }
+}
+
+
+// Standard
+// flags, header size, EncryptionHeader (algorithm, key size), then
+// EncryptionVerifier (salt, encrypted verifier and its hash), AES in ECB mode.
+struct Standard {
+    key_bytes: usize,
+    salt: Vec<u8>,
+    verifier: Vec<u8>,
+    verifier_hash: Vec<u8>
+}
+
+impl Standard {
+    fn parse(info: &[u8]) -> Result<Standard, MsoError> {
+        let flags = le_u32(info, 4);
+        if flags & FLAG_AES == 0 {
+            return Err(MsoError::UnsupportedEncryption("RC4".to_owned()));
+        }
+
+        let header_size = le_u32(info, 8) as usize;
+        let key_bits = le_u32(info, 12 + 16) as usize;
+        let verifier = 12 + header_size;
+        let salt_size = le_u32(info, verifier) as usize;
+        let slice = |start: usize, length: usize| {
+            info.get(start..start.saturating_add(length))
+                .map(|bytes| bytes.to_vec())
+                .ok_or_else(|| MsoError::BadCompoundFile("truncated encryption info".to_owned()))
+        };
+
+        Ok(Standard {
+            key_bytes: key_bits / 8,
+            salt: slice(verifier + 4, salt_size)?,
+            verifier: slice(verifier + 4 + salt_size, 16)?,
+            verifier_hash: slice(verifier + 4 + salt_size + 16 + 4, 32)?
+        })
+    }
+
+    fn secret_key(self, password: &str) -> Result<SecretKey, MsoError> {
+        let hash = Hash::Sha1;
+        let spun = hash.spin(&self.salt, password, STANDARD_SPIN_COUNT);
+        let derived = hash.digest(&[&spun, &0u32.to_le_bytes()]);
+
+        let mut buffer1 = [0x36u8; 64];
+        let mut buffer2 = [0x5cu8; 64];
+        for (i, byte) in derived.iter().enumerate() {
+            buffer1[i] ^= byte;
+            buffer2[i] ^= byte;
+        }
+        let mut key = hash.digest(&[&buffer1]);
+        key.extend(hash.digest(&[&buffer2]));
+        key.truncate(self.key_bytes);
+
+        let cipher = Cipher::new(&key)?;
+        let mut verifier = self.verifier.clone();
+        cipher.decrypt_block(&mut verifier);
+        let mut verifier_hash = self.verifier_hash.clone();
+        for block in verifier_hash.chunks_mut(16) {
+            cipher.decrypt_block(block);
+        }
+        if hash.digest(&[&verifier])[..] != verifier_hash[..20] {
+            return Err(MsoError::WrongPassword);
+        }
+
+        Ok(SecretKey::Standard(key))
+    }
+}
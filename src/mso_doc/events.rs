@@ -0,0 +1,378 @@
+// Pull events
+// The content of a document as an iterator of events, for the callers
+// composing iterator adapters rather than implementing a WordReader.
+// The main part of a docx is read by the states of its parser, resumed on
+// each call:
+//   body
+//     - paragraph, its start sent with the style and heading level, then
+//       a text per run, unless the style filter leaves it out
+//     - table
+//       - row
+//         - cell, one per paragraph as with WordReader
+// Images follow the end of their paragraph or cell.
+// Other formats are parsed in full with their WordReader, their events
+// being buffered.
+use std::collections::VecDeque;
+use std::vec;
+
+use mso_doc::*;
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DocEvent {
+    // role of the style map, other than a heading
+    ParagraphStart { style: String, level: Option<u32>, role: Option<Role> },
+    Text(String),
+    ParagraphEnd,
+    TableStart,
+    RowStart,
+    Cell { text: String, style: String, header: bool },
+    RowEnd,
+    TableEnd,
+    SectionBreak,
+    Note { kind: NoteKind, id: String, text: String },
+    Image
+}
+
+pub struct Events<'a> {
+    source: Source<'a>
+}
+
+enum Source<'a> {
+    Docx(Box<DocxEvents<'a>>),
+    Buffered(vec::IntoIter<DocEvent>),
+    Failed(Option<MsoError>)
+}
+
+impl<'a> Iterator for Events<'a> {
+    type Item = Result<DocEvent, MsoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.source {
+            Source::Docx(ref mut events) => events.next(),
+            Source::Buffered(ref mut events) => events.next().map(Ok),
+            Source::Failed(ref mut err) => err.take().map(Err)
+        }
+    }
+}
+
+// Events of the document, a failure to open it being the only item.
+pub fn events<'a, R>(source: R, options: &Options) -> Events<'a>
+    where R: io::Read + io::Seek + 'a {
+        let source = match open(source, options) {
+            Ok(source) => source,
+            Err(err) => Source::Failed(Some(err))
+        };
+        Events { source }
+    }
+
+fn open<'a, R>(mut source: R, options: &Options) -> Result<Source<'a>, MsoError>
+    where R: io::Read + io::Seek + 'a {
+        let mut magic = Vec::new();
+        (&mut source).take(4).read_to_end(&mut magic)?;
+        source.seek(io::SeekFrom::Start(0))?;
+
+        if magic.starts_with(b"PK") {
+            let package = opc::Package::open(source, &options.limits)?;
+            if !package.has_part("/mimetype") {
+                return DocxEvents::open(package, options).map(|events| Source::Docx(Box::new(events)));
+            }
+            let mut collector = Collector::default();
+            read_package(package, options, &mut collector)?;
+            return Ok(Source::Buffered(collector.finish().into_iter()));
+        }
+
+        let mut collector = Collector::default();
+        parse_reader(source, options, &mut collector)?;
+        Ok(Source::Buffered(collector.finish().into_iter()))
+    }
+
+
+// Parser of the docx main part, run a state at a time as the events are
+// asked for, and what its states sent so far.
+struct DocxEvents<'a> {
+    xml: sax_docx::UntilError<limits::XmlPart<Box<dyn io::Read + 'a>>>,
+    parser: sax_docx::DocParser,
+    queue: Queue,
+    // sent after the body
+    notes: Vec<DocEvent>,
+    done: bool
+}
+
+impl<'a> DocxEvents<'a> {
+    fn open<R>(mut package: opc::Package<R>, options: &Options) -> Result<DocxEvents<'a>, MsoError>
+        where R: io::Read + io::Seek + 'a {
+            let main_part = package.main_document()?;
+            let styles = parse_styles(&mut package, &main_part)?;
+
+            let mut notes = Collector::default();
+            parse_notes(&mut package, &main_part, options, &mut notes)?;
+
+            let mut parser = sax_docx::DocParser::new("root");
+            parser.debug = options.debug;
+            Ok(DocxEvents {
+                xml: sax_docx::UntilError::new(limits::XmlPart::new(package.into_part(&main_part)?)),
+                parser,
+                queue: Queue {
+                    events: VecDeque::new(),
+                    styling: Styling {
+                        styles,
+                        filter: options.style_filter.clone(),
+                        style_map: options.style_map.clone()
+                    },
+                    cell_depth: 0,
+                    in_table: false,
+                    in_row: false,
+                    row_header: false,
+                    paragraph: None
+                },
+                notes: notes.finish(),
+                done: false
+            })
+        }
+
+    fn next(&mut self) -> Option<Result<DocEvent, MsoError>> {
+        loop {
+            if let Some(event) = self.queue.events.pop_front() {
+                return Some(Ok(event));
+            }
+            if self.done {
+                return self.xml.error.take().map(|err| Err(err.into()));
+            }
+
+            if !self.parser.step(&mut self.xml, &mut self.queue) {
+                self.done = true;
+                if self.xml.error.is_none() {
+                    self.queue.close_table();
+                    self.queue.events.extend(self.notes.drain(..));
+                }
+            }
+        }
+    }
+}
+
+// Events sent by the states of the docx parser, the text of a paragraph
+// following its start, and the text of a cell sent whole. The rows end
+// with the next one or their table.
+struct Queue {
+    events: VecDeque<DocEvent>,
+    styling: Styling,
+    // cells open, of the outermost table and of the nested ones
+    cell_depth: u32,
+    in_table: bool,
+    in_row: bool,
+    row_header: bool,
+    // whether the style filter keeps the paragraph started
+    paragraph: Option<bool>
+}
+
+impl Queue {
+    fn start(&mut self, paragraph: &sax_docx::Paragraph) {
+        let (kept, start) = self.styling.start(&paragraph.style, paragraph.outline);
+        self.paragraph = Some(kept);
+        self.events.extend(start);
+    }
+
+    fn send_images(&mut self, paragraph: &sax_docx::Paragraph) {
+        self.events.extend((0..paragraph.images).map(|_| DocEvent::Image));
+    }
+
+    fn close_row(&mut self) {
+        if self.in_row {
+            self.events.push_back(DocEvent::RowEnd);
+            self.in_row = false;
+        }
+    }
+
+    fn close_table(&mut self) {
+        self.close_row();
+        if self.in_table {
+            self.events.push_back(DocEvent::TableEnd);
+            self.in_table = false;
+        }
+    }
+}
+
+impl sax_docx::Sink for Queue {
+    // the paragraphs of text boxes belong to the paragraph started
+    fn paragraph_start(&mut self, paragraph: &sax_docx::Paragraph) {
+        if self.cell_depth == 0 && self.paragraph.is_none() {
+            self.start(paragraph);
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.cell_depth == 0 && self.paragraph == Some(true) {
+            self.events.push_back(DocEvent::Text(text.to_owned()));
+        }
+    }
+
+    fn paragraph(&mut self, paragraph: &sax_docx::Paragraph) {
+        if self.paragraph.is_none() {
+            self.start(paragraph);
+        }
+        if self.paragraph.take() == Some(true) {
+            self.events.push_back(DocEvent::ParagraphEnd);
+            self.send_images(paragraph);
+        }
+        if paragraph.section_end {
+            self.events.push_back(DocEvent::SectionBreak);
+        }
+    }
+
+    fn row_start(&mut self, header: bool) {
+        if self.cell_depth == 0 {
+            if !self.in_table {
+                self.events.push_back(DocEvent::TableStart);
+                self.in_table = true;
+            }
+            self.close_row();
+            self.events.push_back(DocEvent::RowStart);
+            self.in_row = true;
+            self.row_header = header;
+        }
+    }
+
+    fn cell_start(&mut self) {
+        self.cell_depth += 1;
+    }
+
+    // the cells left out are sent empty
+    fn cell(&mut self, paragraph: &sax_docx::Paragraph) {
+        let kept = self.styling.keeps(&paragraph.style).0;
+        self.events.push_back(DocEvent::Cell {
+            text: if kept { paragraph.text.clone() } else { String::new() },
+            style: paragraph.style.clone(),
+            header: self.row_header
+        });
+        if kept {
+            self.send_images(paragraph);
+        }
+    }
+
+    fn cell_end(&mut self) {
+        self.cell_depth = self.cell_depth.saturating_sub(1);
+    }
+
+    fn table_end(&mut self) {
+        if self.cell_depth == 0 {
+            self.close_table();
+        }
+    }
+
+    fn note_end(&mut self, _: &str, _: &str) {}
+}
+
+
+// Heading levels of the paragraphs, and the style filter and map of the
+// options, applied as Filtered does for the readers.
+struct Styling {
+    styles: Styles,
+    filter: StyleFilter,
+    style_map: StyleMap
+}
+
+impl Styling {
+    fn keeps(&self, style: &str) -> (bool, Option<Role>) {
+        style_filter::keeps(&self.filter, &self.style_map, Some(&self.styles), style)
+    }
+
+    // Whether the paragraph is kept, and its start if so.
+    fn start(&self, style: &str, outline: Option<u32>) -> (bool, Option<DocEvent>) {
+        let (kept, role) = self.keeps(style);
+        if !kept {
+            return (false, None);
+        }
+        let (level, role) = style_filter::paragraph_role(role, self.styles.paragraph_level(style, outline));
+        (true, Some(DocEvent::ParagraphStart { style: style.to_owned(), level, role }))
+    }
+}
+
+
+// Reader buffering the events of a parse.
+#[derive(Default)]
+struct Collector {
+    events: Vec<DocEvent>,
+    in_table: bool,
+    in_row: bool
+}
+
+impl Collector {
+    fn finish(mut self) -> Vec<DocEvent> {
+        self.close_table();
+        self.events
+    }
+
+    fn close_row(&mut self) {
+        if self.in_row {
+            self.events.push(DocEvent::RowEnd);
+            self.in_row = false;
+        }
+    }
+
+    fn close_table(&mut self) {
+        self.close_row();
+        if self.in_table {
+            self.events.push(DocEvent::TableEnd);
+            self.in_table = false;
+        }
+    }
+
+    fn push_paragraph(&mut self, text: &str, style: &str, level: Option<u32>, role: Option<Role>) {
+        self.close_table();
+        self.events.push(DocEvent::ParagraphStart { style: style.to_owned(), level, role });
+        if !text.is_empty() {
+            self.events.push(DocEvent::Text(text.to_owned()));
+        }
+        self.events.push(DocEvent::ParagraphEnd);
+    }
+}
+
+impl WordReader for Collector {
+    fn paragraph_row(&mut self, text: &str, style: &str) {
+        self.push_paragraph(text, style, None, None);
+    }
+
+    fn heading_row(&mut self, text: &str, style: &str, level: u32) {
+        self.push_paragraph(text, style, Some(level), None);
+    }
+
+    fn role_row(&mut self, text: &str, style: &str, role: Role) {
+        self.push_paragraph(text, style, None, Some(role));
+    }
+
+    fn table_new_row(&mut self) {
+        if !self.in_table {
+            self.events.push(DocEvent::TableStart);
+            self.in_table = true;
+        }
+        self.close_row();
+        self.events.push(DocEvent::RowStart);
+        self.in_row = true;
+    }
+
+    fn table_closed(&mut self) {
+        self.close_table();
+    }
+
+    fn table_cell(&mut self, text: &str, style: &str, header: bool) {
+        if !self.in_row {
+            self.table_new_row();
+        }
+        self.events.push(DocEvent::Cell { text: text.to_owned(), style: style.to_owned(), header });
+    }
+
+    fn section_break(&mut self) {
+        self.close_table();
+        self.events.push(DocEvent::SectionBreak);
+    }
+
+    fn note(&mut self, kind: NoteKind, id: &str, text: &str) {
+        self.close_table();
+        self.events.push(DocEvent::Note { kind, id: id.to_owned(), text: text.to_owned() });
+    }
+
+    fn image(&mut self) {
+        self.events.push(DocEvent::Image);
+    }
+}
@@ -0,0 +1,424 @@
+// Single file Word XML
+// Flat OPC ("Word XML Document"), every part of the package inlined:
+//   <pkg:package>
+//     <pkg:part pkg:name="/_rels/.rels" pkg:contentType="...">
+//       <pkg:xmlData>...</pkg:xmlData>
+//     </pkg:part>
+// Word 2003 XML, the body directly under <w:wordDocument>, with headers,
+// footers and notes inlined where they are referenced, and pictures in
+// <w:pict> as for docx.
+use std::io;
+use std::collections::HashMap;
+
+use mso_doc::*;
+
+
+// Few leading bytes, after a possible byte order mark and blanks, start
+// an XML document.
+pub fn is_xml(magic: &[u8]) -> bool {
+    let magic = if magic.starts_with(b"\xEF\xBB\xBF") { &magic[3..] } else { magic };
+    magic.iter()
+        .find(|c| !c.is_ascii_whitespace())
+        .map(|&c| c == b'<')
+        .unwrap_or(false)
+}
+
+pub fn parse<R: io::Read>(mut source: R, options: &Options, reader: &mut dyn WordReader) -> Result<(), MsoError> {
+    let mut content = Vec::new();
+    source.read_to_end(&mut content)?;
+
+    let root = root_element(&content)?;
+    if root.is_tag("pkg:package") {
+        let package = Package::scan(&content)?;
+        let main_part = package.main_document(&content, &options.limits)?;
+        let relationships = package.relationships(&content, &main_part, &options.limits)?;
+        let styles = package.styles(&content, &relationships, &options.limits)?;
+        let mut reader = Filtered::new(reader, options, Some(&styles));
+        {
+            let mut events = package.part(&content, &main_part, &options.limits)?
+                .ok_or_else(|| MsoError::MissingPart(main_part.clone()))?;
+            sax_docx::DocumentRoot::new(options.debug).parse(&mut events, &styles, &mut reader)?;
+        }
+
+        // footnotes and endnotes, as for a zipped package
+        for relationship in &relationships {
+            let kind = match note_kind(relationship) {
+                Some(kind) if !relationship.external => kind,
+                _ => continue
+            };
+            if let Some(mut events) = package.part(&content, &relationship.target, &options.limits)? {
+                sax_docx::DocumentRoot::new(options.debug).parse_notes(&mut events, kind, &mut reader)?;
+            }
+        }
+        Ok(())
+    } else if root.is_tag("w:wordDocument") {
+        let styles = word2003_styles(&content)?;
+        let mut events = limits::XmlPart::uncompressed(content.as_slice(), "document", &options.limits);
+        let mut reader = Filtered::new(reader, options, Some(&styles));
+        let mut events = Word2003Events::new(&mut events);
+        sax_docx::DocumentRoot::new(options.debug).parse(&mut events, &styles, &mut reader)?;
+
+        // notes inlined where they are referenced, sent after the body
+        for &kind in &[NoteKind::Footnote, NoteKind::Endnote] {
+            sax_docx::DocumentRoot::new(options.debug).parse_notes(&mut events.notes(kind), kind, &mut reader)?;
+        }
+        Ok(())
+    } else {
+        Err(MsoError::UnsupportedContent(format!("xml root element {}", root)))
+    }
+}
+
+fn root_element(content: &[u8]) -> Result<OwnedName, MsoError> {
+    for event in xml::reader::EventReader::new(content) {
+        if let XmlEvent::StartElement { name, .. } = event? {
+            return Ok(name);
+        }
+    }
+    Err(MsoError::UnsupportedContent("xml without element".to_owned()))
+}
+
+// Styles of a Word 2003 document, inlined before its body.
+fn word2003_styles(content: &[u8]) -> Result<Styles, MsoError> {
+    let mut events = xml::reader::EventReader::new(content).into_iter();
+    while let Some(event) = events.next() {
+        if let XmlEvent::StartElement { ref name, .. } = event? {
+            if name.is_tag("w:styles") {
+                return Styles::parse(&mut events);
+            } else if name.is_tag("w:body") {
+                break;
+            }
+        }
+    }
+    Ok(Styles::default())
+}
+
+
+// Package
+// Content types of the inlined parts, read from their start tags, and
+// where each one starts, found by scanning the bytes for these tags. A
+// part is then read from its offset, after a copy of the root element
+// declaring the namespaces, and of an empty part start tag.
+#[derive(Default)]
+struct Package {
+    // lowercase part name -> content type
+    content_types: HashMap<String, String>,
+    // lowercase part name -> offset just after its <pkg:part> start tag
+    offsets: HashMap<String, usize>,
+    // <pkg:package ...><pkg:part> start tags, the first one with the
+    // namespaces of the root element
+    start_tags: String
+}
+
+impl Package {
+    fn is_part_tag(name: &OwnedName) -> bool { name.is_tag("pkg:part") }
+
+    fn scan(content: &[u8]) -> Result<Package, MsoError> {
+        let mut package = Package::default();
+        // part name and content type, in document order
+        let mut parts = Vec::new();
+        let mut part_tag = None;
+
+        for event in xml::reader::EventReader::new(content) {
+            if let XmlEvent::StartElement { ref name, ref attributes, ref namespace } = event? {
+                if name.is_tag("pkg:package") {
+                    package.start_tags = root_tag(name, namespace);
+                } else if Self::is_part_tag(name) {
+                    if part_tag.is_none() {
+                        let tag = name.borrow().to_repr();
+                        package.start_tags.push_str(&format!("<{}>", tag));
+                        part_tag = Some(tag);
+                    }
+                    parts.push((attributes.value("pkg:name").to_lowercase(), attributes.value("pkg:contentType")));
+                }
+            }
+        }
+
+        let offsets = part_tag.map(|tag| start_tag_ends(content, &tag)).unwrap_or_default();
+        if offsets.len() != parts.len() {
+            return Err(MsoError::UnsupportedContent("flat package with misplaced parts".to_owned()));
+        }
+        for ((part, content_type), offset) in parts.into_iter().zip(offsets) {
+            package.offsets.insert(part.clone(), offset);
+            package.content_types.insert(part, content_type);
+        }
+        Ok(package)
+    }
+
+    // Events of the XML content of the part, if found.
+    fn part<'a>(&'a self, content: &'a [u8], part: &str, limits: &Limits)
+        -> Result<Option<InlinedPart<'a>>, MsoError> {
+            let offset = match self.offsets.get(&part.to_lowercase()) {
+                Some(&offset) => offset,
+                None => return Ok(None)
+            };
+            let source = self.start_tags.as_bytes().chain(&content[offset..]);
+            let mut events = limits::XmlPart::uncompressed(source, part, limits);
+            if !Package::seek_part(&mut events)? {
+                return Ok(None);
+            }
+            Ok(Some(PartEvents::new(events)))
+        }
+
+    fn main_document(&self, content: &[u8], limits: &Limits) -> Result<String, MsoError> {
+        let relationships = match self.part(content, &opc::relationships_part("/"), limits)? {
+            Some(mut events) => opc::read_relationships("/", &mut events)?,
+            None => Vec::new()
+        };
+
+        let main_part = opc::main_part(&relationships);
+        match self.content_types.get(&main_part.to_lowercase()) {
+            Some(content_type) => opc::check_main_content_type(Some(content_type))?,
+            None => return Err(MsoError::MissingPart(main_part))
+        }
+        Ok(main_part)
+    }
+
+    // Moves the events just after the start tag of the part.
+    fn seek_part<T>(source: &mut T) -> Result<bool, MsoError>
+        where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+            for event in source {
+                if let XmlEvent::StartElement { ref name, .. } = event? {
+                    if Self::is_part_tag(name) {
+                        return Ok(true);
+                    }
+                }
+            }
+            Ok(false)
+        }
+
+    // Relationships of a part, none if it has no relationships part.
+    fn relationships(&self, content: &[u8], part: &str, limits: &Limits) -> Result<Vec<opc::Relationship>, MsoError> {
+        match self.part(content, &opc::relationships_part(part), limits)? {
+            Some(mut events) => opc::read_relationships(part, &mut events),
+            None => Ok(Vec::new())
+        }
+    }
+
+    // Styles related to the main part, if any.
+    fn styles(&self, content: &[u8], relationships: &[opc::Relationship], limits: &Limits) -> Result<Styles, MsoError> {
+        let styles_part = match opc::styles_part(relationships) {
+            Some(styles_part) => styles_part,
+            None => return Ok(Styles::default())
+        };
+        match self.part(content, &styles_part, limits)? {
+            Some(mut events) => Styles::parse(&mut events),
+            None => Ok(Styles::default())
+        }
+    }
+}
+
+// Events of a part, after the start tags preceding it.
+type InlinedPart<'a> = PartEvents<limits::XmlPart<io::Chain<&'a [u8], &'a [u8]>>>;
+
+// Start tag of the root element, declaring its namespaces again.
+fn root_tag(name: &OwnedName, namespace: &xml::namespace::Namespace) -> String {
+    let mut tag = format!("<{}", name.borrow().to_repr());
+    for (prefix, uri) in namespace {
+        match prefix {
+            xml::namespace::NS_XML_PREFIX | xml::namespace::NS_XMLNS_PREFIX => continue,
+            xml::namespace::NS_NO_PREFIX if uri.is_empty() => continue,
+            xml::namespace::NS_NO_PREFIX => tag.push_str(" xmlns=\""),
+            prefix => {
+                tag.push_str(" xmlns:");
+                tag.push_str(prefix);
+                tag.push_str("=\"");
+            }
+        }
+        tag.push_str(&xml::escape::escape_str_attribute(uri));
+        tag.push('"');
+    }
+    tag.push('>');
+    tag
+}
+
+// Offsets just after the start tags of the given name, in document order.
+// Comments, CDATA sections, processing instructions and declarations are
+// skipped, and no markup is left in text or attribute values of a well
+// formed document.
+fn start_tag_ends(content: &[u8], tag: &str) -> Vec<usize> {
+    let mut offsets = Vec::new();
+    let mut i = 0;
+
+    while let Some(start) = find(content, i, b"<") {
+        let markup = &content[start..];
+        i = if markup.starts_with(b"<!--") {
+            end_of(content, start + 4, b"-->")
+        } else if markup.starts_with(b"<![CDATA[") {
+            end_of(content, start + 9, b"]]>")
+        } else if markup.starts_with(b"<?") {
+            end_of(content, start + 2, b"?>")
+        } else if markup.starts_with(b"<!") {
+            end_of(content, start + 2, b">")
+        } else {
+            let name_end = markup.iter()
+                .position(|&c| c.is_ascii_whitespace() || c == b'/' || c == b'>')
+                .map_or(content.len(), |n| start + n);
+            let end = tag_end(content, name_end);
+            if &content[start + 1..name_end] == tag.as_bytes() && end <= content.len() {
+                offsets.push(end);
+            }
+            end
+        };
+    }
+    offsets
+}
+
+fn find(content: &[u8], from: usize, pattern: &[u8]) -> Option<usize> {
+    content.get(from..)
+        .and_then(|rest| rest.windows(pattern.len()).position(|window| window == pattern))
+        .map(|i| from + i)
+}
+
+// Offset after the end of the markup, or the end of the content if not
+// closed.
+fn end_of(content: &[u8], from: usize, end: &[u8]) -> usize {
+    find(content, from, end).map_or(content.len(), |i| i + end.len())
+}
+
+// Offset after the '>' closing a tag, outside of its quoted attribute
+// values.
+fn tag_end(content: &[u8], from: usize) -> usize {
+    let mut quote = None;
+    for (i, &c) in content.iter().enumerate().skip(from) {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => (),
+            None if c == b'"' || c == b'\'' => quote = Some(c),
+            None if c == b'>' => return i + 1,
+            None => ()
+        }
+    }
+    content.len()
+}
+
+// Events of the XML content of the current part, ending with it.
+struct PartEvents<T> {
+    source: T,
+    done: bool
+}
+
+impl<T> PartEvents<T>
+    where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+        fn new(source: T) -> PartEvents<T> {
+            PartEvents { source, done: false }
+        }
+    }
+
+impl<T> Iterator for PartEvents<T>
+    where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+        type Item = Result<XmlEvent, xml::reader::Error>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            if self.done {
+                return None;
+            }
+
+            match self.source.next() {
+                Some(Ok(XmlEvent::EndElement { ref name }))
+                if Package::is_part_tag(name) => {
+                    self.done = true;
+                    None
+                },
+                other => other
+            }
+        }
+    }
+
+// Events of a Word 2003 document, without the headers, footers and notes
+// which would otherwise appear as paragraphs nested in the body. The notes
+// are kept aside, numbered in document order as Word does.
+struct Word2003Events<'a, T: 'a> {
+    source: &'a mut T,
+    skipped: Option<(OwnedName, u32)>,
+    note: Option<(NoteKind, Vec<XmlEvent>)>,
+    notes: Vec<(NoteKind, Vec<XmlEvent>)>,
+    numbers: [u32; 2]
+}
+
+impl<'a, T> Word2003Events<'a, T>
+    where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+        fn new(source: &'a mut T) -> Word2003Events<'a, T> {
+            Word2003Events { source, skipped: None, note: None, notes: Vec::new(), numbers: [0; 2] }
+        }
+
+        fn is_skipped_tag(name: &OwnedName) -> bool {
+            name.is_tag("w:hdr") || name.is_tag("w:ftr") || Self::note_kind(name).is_some()
+        }
+
+        fn note_kind(name: &OwnedName) -> Option<NoteKind> {
+            if name.is_tag("w:footnote") {
+                Some(NoteKind::Footnote)
+            } else if name.is_tag("w:endnote") {
+                Some(NoteKind::Endnote)
+            } else {
+                None
+            }
+        }
+
+        // Start of a note, given the id of its number unless a separator.
+        fn start_note(&mut self, kind: NoteKind, event: &XmlEvent) {
+            let mut event = event.clone();
+            if let XmlEvent::StartElement { ref name, ref mut attributes, .. } = event {
+                if attributes.value("w:type").is_empty() {
+                    let number = &mut self.numbers[kind as usize];
+                    *number += 1;
+                    let id = OwnedName {
+                        local_name: "id".to_owned(),
+                        namespace: name.namespace.clone(),
+                        prefix: name.prefix.clone()
+                    };
+                    attributes.push(OwnedAttribute::new(id, number.to_string()));
+                }
+            }
+            self.note = Some((kind, vec![event]));
+        }
+
+        // Events of the notes of the given kind, as a notes part would
+        // hold them.
+        fn notes(&self, kind: NoteKind) -> impl Iterator<Item=Result<XmlEvent, xml::reader::Error>> + '_ {
+            self.notes.iter()
+                .filter(move |&&(note_kind, _)| note_kind == kind)
+                .flat_map(|(_, events)| events.iter().cloned().map(Ok))
+        }
+    }
+
+impl<'a, T> Iterator for Word2003Events<'a, T>
+    where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+        type Item = Result<XmlEvent, xml::reader::Error>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            loop {
+                let event = self.source.next();
+                if let (&mut Some((_, ref mut events)), &Some(Ok(ref event))) = (&mut self.note, &event) {
+                    events.push(event.clone());
+                }
+                match (&mut self.skipped, &event) {
+                    (&mut None, &Some(Ok(XmlEvent::StartElement { ref name, .. })))
+                    if Self::is_skipped_tag(name) => {
+                        self.skipped = Some((name.clone(), 0));
+                        if let Some(kind) = Self::note_kind(name) {
+                            if let Some(Ok(ref event)) = event {
+                                self.start_note(kind, event);
+                            }
+                        }
+                    },
+                    (&mut None, _) => return event,
+                    (&mut Some((ref tag, ref mut depth)), &Some(Ok(XmlEvent::StartElement { ref name, .. })))
+                    if name == tag => *depth += 1,
+                    (&mut Some((ref tag, ref mut depth)), &Some(Ok(XmlEvent::EndElement { ref name })))
+                    if name == tag => {
+                        if *depth == 0 {
+                            self.skipped = None;
+                            if let Some(note) = self.note.take() {
+                                self.notes.push(note);
+                            }
+                        } else {
+                            *depth -= 1;
+                        }
+                    },
+                    (&mut Some(_), &Some(Ok(_))) => (),
+                    (&mut Some(_), _) => return event
+                }
+            }
+        }
+    }
@@ -0,0 +1,252 @@
+// Resource limits
+// Bounds on what an untrusted document may expand to, checked as the
+// parts are decompressed and their XML read rather than trusted from the
+// sizes the zip headers declare:
+//   package    number of parts
+//   part       uncompressed size, and ratio to the compressed size
+//   xml        depth of the elements, and text of a paragraph (any <p>
+//              or <h> element, as w:p, text:p and text:h)
+//   sheets     cells sent for a spreadsheet, its repeated rows and cells
+//              expanded
+// Legacy binary and RTF documents are read whole, their content being
+// bounded by the size of the file itself.
+use std::io;
+use std::fmt;
+use std::error;
+
+use mso_doc::*;
+
+
+// Ratios are only checked past this size, since small parts of repeated
+// markup compress well beyond any sensible ratio.
+const RATIO_FREE_SIZE: u64 = 1 << 20;
+
+
+#[derive(Clone, Debug)]
+pub struct Limits {
+    // uncompressed bytes of a part
+    pub max_part_size: u64,
+    // uncompressed bytes of a part per compressed byte
+    pub max_compression_ratio: u64,
+    pub max_parts: usize,
+    // nesting of the XML elements
+    pub max_depth: usize,
+    // bytes of text of a paragraph, text boxes included
+    pub max_paragraph_length: usize,
+    // cells of the tables of a spreadsheet, repetitions included
+    pub max_cells: u64
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        Limits {
+            max_part_size: 256 << 20,
+            max_compression_ratio: 200,
+            max_parts: 10_000,
+            max_depth: 256,
+            max_paragraph_length: 16 << 20,
+            max_cells: 10_000_000
+        }
+    }
+}
+
+impl Limits {
+    pub fn check_parts(&self, count: usize) -> Result<(), MsoError> {
+        if count > self.max_parts {
+            return Err(MsoError::LimitExceeded(format!("{} parts, over {}", count, self.max_parts)));
+        }
+        Ok(())
+    }
+
+    // Text of a paragraph expanded by the parser, as the repeated spaces
+    // of OpenDocument.
+    pub fn check_paragraph_length(&self, length: usize) -> Result<(), MsoError> {
+        if length > self.max_paragraph_length {
+            return Err(MsoError::LimitExceeded(paragraph_exceeded(self.max_paragraph_length)));
+        }
+        Ok(())
+    }
+
+    pub fn check_cells(&self, count: u64) -> Result<(), MsoError> {
+        if count > self.max_cells {
+            return Err(MsoError::LimitExceeded(format!("over {} cells", self.max_cells)));
+        }
+        Ok(())
+    }
+}
+
+fn paragraph_exceeded(max_paragraph_length: usize) -> String {
+    format!("paragraph over {} bytes of text", max_paragraph_length)
+}
+
+
+// Limit exceeded while reading, carried by an io::Error up to the parse
+// functions which turn it into MsoError::LimitExceeded.
+#[derive(Debug, Clone)]
+pub struct Exceeded(pub String);
+
+impl fmt::Display for Exceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl error::Error for Exceeded {}
+
+impl Exceeded {
+    pub fn of_io(err: &io::Error) -> Option<&Exceeded> {
+        err.get_ref().and_then(|inner| inner.downcast_ref::<Exceeded>())
+    }
+
+    fn into_io(self) -> io::Error {
+        io::Error::other(self)
+    }
+}
+
+
+// Content of a part, failing once it decompresses past the limits.
+pub struct PartReader<R: io::Read> {
+    source: R,
+    name: String,
+    compressed_size: u64,
+    read: u64,
+    limits: Limits,
+    exceeded: Option<Exceeded>
+}
+
+impl<R: io::Read> PartReader<R> {
+    pub fn new(source: R, name: &str, compressed_size: u64, limits: &Limits) -> PartReader<R> {
+        PartReader {
+            source,
+            name: name.to_owned(),
+            compressed_size,
+            read: 0,
+            limits: limits.clone(),
+            exceeded: None
+        }
+    }
+
+    // Limit exceeded, the XML reader only keeping the message of the
+    // errors of its source.
+    pub fn exceeded(&self) -> Option<&Exceeded> {
+        self.exceeded.as_ref()
+    }
+
+    fn check(&self) -> Option<Exceeded> {
+        if self.read > self.limits.max_part_size {
+            Some(Exceeded(format!("part {} over {} bytes", self.name, self.limits.max_part_size)))
+        } else if self.read > RATIO_FREE_SIZE
+            && self.read / self.compressed_size.max(1) > self.limits.max_compression_ratio {
+            Some(Exceeded(format!("part {} compressed over {} times", self.name, self.limits.max_compression_ratio)))
+        } else {
+            None
+        }
+    }
+}
+
+impl<R: io::Read> io::Read for PartReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(ref exceeded) = self.exceeded {
+            return Err(exceeded.clone().into_io());
+        }
+
+        let count = self.source.read(buf)?;
+        self.read += count as u64;
+        if let Some(exceeded) = self.check() {
+            self.exceeded = Some(exceeded.clone());
+            return Err(exceeded.into_io());
+        }
+        Ok(count)
+    }
+}
+
+
+// XML events of a part, failing on elements nested too deep or on
+// paragraphs of too much text.
+pub struct XmlPart<R: io::Read> {
+    events: xml::reader::Events<io::BufReader<PartReader<R>>>,
+    limits: Limits,
+    depth: usize,
+    // nesting of the paragraphs, and text of the outermost one
+    paragraph_depth: usize,
+    paragraph_length: usize,
+    done: bool
+}
+
+impl<R: io::Read> XmlPart<R> {
+    pub fn new(source: PartReader<R>) -> XmlPart<R> {
+        let limits = source.limits.clone();
+        XmlPart {
+            events: xml::reader::EventReader::new(io::BufReader::new(source)).into_iter(),
+            limits,
+            depth: 0,
+            paragraph_depth: 0,
+            paragraph_length: 0,
+            done: false
+        }
+    }
+
+    // XML read from an uncompressed source, as a flat XML document.
+    pub fn uncompressed(source: R, name: &str, limits: &Limits) -> XmlPart<R> {
+        XmlPart::new(PartReader::new(source, name, u64::MAX, limits))
+    }
+
+    fn is_paragraph(name: &OwnedName) -> bool {
+        name.local_name == "p" || name.local_name == "h"
+    }
+
+    fn check(&mut self, event: &XmlEvent) -> Option<Exceeded> {
+        match *event {
+            XmlEvent::StartElement { ref name, .. } => {
+                self.depth += 1;
+                if self.depth > self.limits.max_depth {
+                    return Some(Exceeded(format!("xml elements nested over {} levels", self.limits.max_depth)));
+                }
+                if Self::is_paragraph(name) {
+                    if self.paragraph_depth == 0 {
+                        self.paragraph_length = 0;
+                    }
+                    self.paragraph_depth += 1;
+                }
+            },
+            XmlEvent::EndElement { ref name } => {
+                self.depth = self.depth.saturating_sub(1);
+                if Self::is_paragraph(name) {
+                    self.paragraph_depth = self.paragraph_depth.saturating_sub(1);
+                }
+            },
+            XmlEvent::Characters(ref text) | XmlEvent::CData(ref text) | XmlEvent::Whitespace(ref text)
+            if self.paragraph_depth > 0 => {
+                self.paragraph_length += text.len();
+                if self.paragraph_length > self.limits.max_paragraph_length {
+                    return Some(Exceeded(paragraph_exceeded(self.limits.max_paragraph_length)));
+                }
+            },
+            _ => ()
+        }
+        None
+    }
+}
+
+impl<R: io::Read> Iterator for XmlPart<R> {
+    type Item = Result<XmlEvent, xml::reader::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let exceeded = match self.events.next()? {
+            Ok(event) => match self.check(&event) {
+                None => return Some(Ok(event)),
+                Some(exceeded) => exceeded
+            },
+            Err(err) => match self.events.source().get_ref().exceeded() {
+                Some(exceeded) => exceeded.clone(),
+                None => return Some(Err(err))
+            }
+        };
+        self.done = true;
+        Some(Err(exceeded.into_io().into()))
+    }
+}
@@ -0,0 +1,816 @@
+extern crate zip;
+extern crate xml;
+extern crate serde;
+
+use std::io;
+use std::io::Read;
+use std::fs;
+use std::path;
+use std::fmt;
+use std::error;
+use std::iter::Iterator;
+
+use self::xml::reader::XmlEvent;
+use self::xml::name::OwnedName;
+use self::xml::attribute::OwnedAttribute;
+
+#[macro_use]
+mod util_parser;
+mod opc;
+mod odt;
+mod ods;
+mod flat_xml;
+mod rtf;
+mod cfb;
+mod doc;
+mod encryption;
+mod events;
+mod limits;
+mod styles;
+mod properties;
+mod style_filter;
+mod style_map;
+
+pub use self::events::{events, DocEvent, Events};
+pub use self::limits::Limits;
+pub use self::properties::{saved_counts, SavedCounts};
+pub use self::style_filter::StyleFilter;
+pub use self::style_map::{Role, StyleMap};
+use self::style_filter::Filtered;
+use self::styles::Styles;
+
+
+pub trait WordReader {
+    fn paragraph_row(&mut self, text: &str, style: &str);
+
+    // Paragraph known to be a heading, with its outline level starting at 1.
+    fn heading_row(&mut self, text: &str, style: &str, _level: u32) {
+        self.paragraph_row(text, style);
+    }
+
+    // Paragraph of a role of the style map other than a heading.
+    fn role_row(&mut self, text: &str, style: &str, _role: Role) {
+        self.paragraph_row(text, style);
+    }
+
+    fn table_new_row(&mut self);
+    fn table_closed(&mut self);
+    fn table_cell(&mut self, text: &str, style: &str, header: bool);
+
+    // End of a cell, once per cell whether it sent one table_cell, several
+    // for its paragraphs, or none.
+    fn table_cell_closed(&mut self) {}
+
+    // End of a section, the next paragraphs starting a new one.
+    fn section_break(&mut self) {}
+
+    // Footnote or endnote, its paragraphs joined by new lines, sent after
+    // the document body.
+    fn note(&mut self, _kind: NoteKind, _id: &str, _text: &str) {}
+
+    // Picture or drawing of the paragraph or cell just sent.
+    fn image(&mut self) {}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteKind {
+    Footnote,
+    Endnote
+}
+
+
+#[derive(Debug)]
+pub enum MsoError {
+    Io(io::Error),
+    Zip(zip::result::ZipError),
+    Xml(xml::reader::Error),
+    MissingPart(String),
+    UnsupportedContent(String),
+    BadCompoundFile(String),
+    BadWordDocument(String),
+    LegacyFormat(&'static str),
+    Encrypted,
+    WrongPassword,
+    UnsupportedEncryption(String),
+    LimitExceeded(String),
+}
+
+impl fmt::Display for MsoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MsoError::Io(ref err) => write!(f, "{}", err),
+            MsoError::Zip(ref err) => write!(f, "bad zip container: {}", err),
+            MsoError::Xml(ref err) => write!(f, "bad xml structure: {}", err),
+            MsoError::MissingPart(ref name) => write!(f, "missing part {}", name),
+            MsoError::UnsupportedContent(ref content) => write!(f, "unsupported content: {}", content),
+            MsoError::BadCompoundFile(ref reason) => write!(f, "bad compound file: {}", reason),
+            MsoError::BadWordDocument(ref reason) => write!(f, "bad Word binary document: {}", reason),
+            MsoError::LegacyFormat(kind) => write!(f, "legacy binary format: {}", kind),
+            MsoError::Encrypted => write!(f, "encrypted document"),
+            MsoError::WrongPassword => write!(f, "wrong password for the encrypted document"),
+            MsoError::UnsupportedEncryption(ref reason) => write!(f, "unsupported encryption: {}", reason),
+            MsoError::LimitExceeded(ref reason) => write!(f, "resource limit exceeded: {}", reason),
+        }
+    }
+}
+
+impl error::Error for MsoError {}
+
+impl From<io::Error> for MsoError {
+    fn from(err: io::Error) -> MsoError {
+        match limits::Exceeded::of_io(&err) {
+            Some(exceeded) => MsoError::LimitExceeded(exceeded.0.clone()),
+            None => MsoError::Io(err)
+        }
+    }
+}
+
+impl From<zip::result::ZipError> for MsoError {
+    fn from(err: zip::result::ZipError) -> MsoError { MsoError::Zip(err) }
+}
+
+impl From<xml::reader::Error> for MsoError {
+    fn from(err: xml::reader::Error) -> MsoError {
+        if let xml::reader::ErrorKind::Io(ref io_err) = *err.kind() {
+            if let Some(exceeded) = limits::Exceeded::of_io(io_err) {
+                return MsoError::LimitExceeded(exceeded.0.clone());
+            }
+        }
+        MsoError::Xml(err)
+    }
+}
+
+
+trait UtilsName {
+    fn is_tag(&self, key: &str) -> bool;
+}
+
+impl UtilsName for OwnedName {
+    fn is_tag(&self, key: &str) -> bool {
+        if let Some(i) = key.find(':') {
+            let (namespace, tail) = key.split_at(i);
+            let (_, key) = tail.split_at(1);
+
+            if let Some(ref p) = self.prefix {
+                p.as_str() == namespace && self.local_name.as_str() == key
+            } else {
+                false
+            }
+        } else if self.prefix.is_none() {
+            self.local_name.as_str() == key
+        } else {
+            false
+        }
+    }
+}
+
+trait UtilsAttributes {
+    fn value(&self, key: &str) -> String;
+}
+
+impl UtilsAttributes for [OwnedAttribute] {
+    fn value(&self, key: &str) -> String {
+        self.iter()
+            .find(|attr| attr.name.is_tag(key))
+            .map(|attr| attr.value.to_owned())
+            .unwrap_or_default()
+    }
+}
+
+// Consumes the events up to the end of the element just started.
+fn skip_element<T>(source: &mut T, tag: &OwnedName) -> Result<(), MsoError>
+    where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+        let mut depth = 0;
+        for event in source {
+            match event? {
+                XmlEvent::StartElement { ref name, .. }
+                if name == tag => depth += 1,
+                XmlEvent::EndElement { ref name, .. }
+                if name == tag => {
+                    if depth == 0 {
+                        break
+                    }
+                    depth -= 1;
+                },
+                _ => ()
+            }
+        }
+        Ok(())
+    }
+
+
+mod sax_docx {
+    use mso_doc::*;
+    use mso_doc::util_parser::{NextAction, ParserState};
+
+    decl_parser!(
+        DocParser,
+        item: XmlEvent,
+        dispatch: next_state,
+        data wrapper: ParsedData,
+        context: dyn Sink,
+        states: [root as Root, body as Body, paragraph as Paragraph, table as Table, row as Row, cell as Cell,
+                 note as Note],
+        phony: [],
+        transitions:
+        root => [body, note],
+        body => [paragraph, table],
+        paragraph => [paragraph],
+        table => [row],
+        row => [cell],
+        cell => [paragraph, table],
+        note => [paragraph, table]
+        );
+
+    // Elements starting a state, anywhere under their parent state.
+    fn state_of(name: &OwnedName) -> Option<&'static str> {
+        if Paragraph::is_tag(name) {
+            Some("paragraph")
+        } else if Table::is_tag(name) {
+            Some("table")
+        } else if Row::is_tag(name) {
+            Some("row")
+        } else if Cell::is_tag(name) {
+            Some("cell")
+        } else if name.is_tag("w:body") {
+            Some("body")
+        } else if Note::is_tag(name) {
+            Some("note")
+        } else {
+            None
+        }
+    }
+
+    fn next_state(event: &XmlEvent) -> NextAction {
+        match *event {
+            XmlEvent::StartElement { ref name, .. } =>
+                state_of(name).map_or(NextAction::Stay, NextAction::ForwardOrStay),
+            XmlEvent::EndElement { ref name } =>
+                state_of(name).map_or(NextAction::Stay, NextAction::DoneIfStay),
+            _ => NextAction::Stay
+        }
+    }
+
+    // Destination of the content, sent by the states as it completes. The
+    // cells of nested tables join the row of their outermost table. The
+    // paragraphs, those of text boxes included, also start once their
+    // settings are read, their text following as it is read.
+    pub trait Sink {
+        fn paragraph_start(&mut self, paragraph: &Paragraph);
+        fn text(&mut self, text: &str);
+        fn paragraph(&mut self, paragraph: &Paragraph);
+        fn row_start(&mut self, header: bool);
+        fn cell_start(&mut self);
+        fn cell(&mut self, paragraph: &Paragraph);
+        fn cell_end(&mut self);
+        fn table_end(&mut self);
+        fn note_end(&mut self, id: &str, note_type: &str);
+    }
+
+    // Paragraphs of a heading style, or with an outline level, are sent as
+    // headings.
+    struct Blocks<'a> {
+        styles: &'a Styles,
+        reader: &'a mut dyn WordReader,
+        // cells open, of the outermost table and of the nested ones
+        cell_depth: u32,
+        row_header: bool
+    }
+
+    impl<'a> Sink for Blocks<'a> {
+        fn paragraph_start(&mut self, _: &Paragraph) {}
+
+        fn text(&mut self, _: &str) {}
+
+        fn paragraph(&mut self, paragraph: &Paragraph) {
+            match self.styles.paragraph_level(&paragraph.style, paragraph.outline) {
+                Some(level) => self.reader.heading_row(&paragraph.text, &paragraph.style, level),
+                None => self.reader.paragraph_row(&paragraph.text, &paragraph.style)
+            }
+            paragraph.send_images(self.reader);
+            if paragraph.section_end {
+                self.reader.section_break();
+            }
+        }
+
+        fn row_start(&mut self, header: bool) {
+            if self.cell_depth == 0 {
+                self.reader.table_new_row();
+                self.row_header = header;
+            }
+        }
+
+        fn cell_start(&mut self) {
+            self.cell_depth += 1;
+        }
+
+        fn cell(&mut self, paragraph: &Paragraph) {
+            self.reader.table_cell(&paragraph.text, &paragraph.style, self.row_header);
+            paragraph.send_images(self.reader);
+        }
+
+        fn cell_end(&mut self) {
+            self.cell_depth = self.cell_depth.saturating_sub(1);
+            if self.cell_depth == 0 {
+                self.reader.table_cell_closed();
+            }
+        }
+
+        fn table_end(&mut self) {
+            if self.cell_depth == 0 {
+                self.reader.table_closed();
+            }
+        }
+
+        fn note_end(&mut self, _: &str, _: &str) {}
+    }
+
+    // Footnotes or endnotes, their paragraphs and cells joined by new
+    // lines, separators left out.
+    struct Notes<'a> {
+        kind: NoteKind,
+        reader: &'a mut dyn WordReader,
+        lines: Vec<String>
+    }
+
+    impl<'a> Sink for Notes<'a> {
+        fn paragraph_start(&mut self, _: &Paragraph) {}
+
+        fn text(&mut self, _: &str) {}
+
+        fn paragraph(&mut self, paragraph: &Paragraph) {
+            self.lines.push(paragraph.text.clone());
+        }
+
+        fn row_start(&mut self, _: bool) {}
+
+        fn cell_start(&mut self) {}
+
+        fn cell(&mut self, paragraph: &Paragraph) {
+            self.lines.push(paragraph.text.clone());
+        }
+
+        fn cell_end(&mut self) {}
+
+        fn table_end(&mut self) {}
+
+        fn note_end(&mut self, id: &str, note_type: &str) {
+            if note_type.is_empty() || note_type == "normal" {
+                self.reader.note(self.kind, id, &self.lines.join("\n"));
+            }
+            self.lines.clear();
+        }
+    }
+
+    // Main part, or notes part, of a docx, sent to the reader as parsed.
+    pub struct DocumentRoot {
+        debug: bool
+    }
+
+    impl DocumentRoot {
+        pub fn new(debug: bool) -> DocumentRoot {
+            DocumentRoot { debug }
+        }
+
+        pub fn parse<T>(&self, source: &mut T, styles: &Styles, reader: &mut dyn WordReader) -> Result<(), MsoError>
+            where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+                self.parse_root(source, &mut Blocks { styles, reader, cell_depth: 0, row_header: false })
+            }
+
+        pub fn parse_notes<T>(&self, source: &mut T, kind: NoteKind, reader: &mut dyn WordReader) -> Result<(), MsoError>
+            where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+                self.parse_root(source, &mut Notes { kind, reader, lines: Vec::new() })
+            }
+
+        fn parse_root<T>(&self, source: &mut T, sink: &mut dyn Sink) -> Result<(), MsoError>
+            where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+                let mut events = UntilError::new(source);
+                let mut parser = DocParser::new("root");
+                parser.debug = self.debug;
+                parser.parse(&mut events, sink);
+                match events.error {
+                    Some(err) => Err(err.into()),
+                    None => Ok(())
+                }
+            }
+    }
+
+    // Events up to the first error, kept for after the parse.
+    pub struct UntilError<T> {
+        source: T,
+        pub error: Option<xml::reader::Error>
+    }
+
+    impl<T> UntilError<T> {
+        pub fn new(source: T) -> UntilError<T> {
+            UntilError { source, error: None }
+        }
+    }
+
+    impl<T> Iterator for UntilError<T>
+        where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+            type Item = XmlEvent;
+
+            fn next(&mut self) -> Option<XmlEvent> {
+                if self.error.is_some() {
+                    return None;
+                }
+                match self.source.next() {
+                    Some(Ok(event)) => Some(event),
+                    Some(Err(err)) => {
+                        self.error = Some(err);
+                        None
+                    },
+                    None => None
+                }
+            }
+        }
+
+    // Root
+    // <w:document>
+    //   - body
+    // or <w:footnotes> and <w:endnotes>
+    //   - note
+    #[derive(Debug, Default)]
+    pub struct Root {
+        // attributes of the note being parsed
+        note_attributes: Option<(String, String)>
+    }
+
+    impl<'s> ParserState<XmlEvent, ParsedData, dyn Sink + 's> for Root {
+        fn read(&mut self, event: &XmlEvent, _: &mut (dyn Sink + 's)) {
+            if let XmlEvent::StartElement { ref name, ref attributes, .. } = *event {
+                if Note::is_tag(name) {
+                    self.note_attributes = Some((attributes.value("w:id"), attributes.value("w:type")));
+                }
+            }
+        }
+
+        fn after_forwards(&mut self, children_data: &mut Vec<ParsedData>, sink: &mut (dyn Sink + 's)) {
+            for child in children_data.drain(..) {
+                if let ParsedData::Note(_) = child {
+                    let (id, note_type) = self.note_attributes.take().unwrap_or_default();
+                    sink.note_end(&id, &note_type);
+                }
+            }
+        }
+    }
+
+    // Body
+    // <w:body>
+    //   - paragraph, sent once read
+    //   - table
+    #[derive(Debug, Default)]
+    pub struct Body;
+
+    impl<'s> ParserState<XmlEvent, ParsedData, dyn Sink + 's> for Body {
+        fn read(&mut self, _: &XmlEvent, _: &mut (dyn Sink + 's)) {
+        }
+
+        fn after_forwards(&mut self, children_data: &mut Vec<ParsedData>, sink: &mut (dyn Sink + 's)) {
+            for child in children_data.drain(..) {
+                if let ParsedData::Paragraph(paragraph) = child {
+                    sink.paragraph(&paragraph);
+                }
+            }
+        }
+    }
+
+    // Paragraph
+    // <w:p>
+    //   - settings, with the style, the outline level, and the section
+    //     settings if it ends one
+    //   - content, started once the settings are read, with the paragraphs
+    //     of text boxes, and the drawings (<w:drawing>, or <w:pict> unless
+    //     the fallback of a drawing)
+    #[derive(Debug, Default)]
+    pub struct Paragraph {
+        pub style: String,
+        pub outline: Option<u32>,
+        pub text: String,
+        pub section_end: bool,
+        pub images: u32,
+        in_properties: bool,
+        started: bool,
+        in_text: bool,
+        in_fallback: u32
+    }
+
+    impl Paragraph {
+        fn is_tag(name: &OwnedName) -> bool { name.is_tag("w:p") }
+
+        fn send_images(&self, reader: &mut dyn WordReader) {
+            for _ in 0..self.images {
+                reader.image();
+            }
+        }
+    }
+
+    impl<'s> ParserState<XmlEvent, ParsedData, dyn Sink + 's> for Paragraph {
+        fn read(&mut self, event: &XmlEvent, sink: &mut (dyn Sink + 's)) {
+            match *event {
+                XmlEvent::StartElement { ref name, ref attributes, .. } => {
+                    if name.is_tag("w:pPr") {
+                        self.in_properties = true;
+                    } else if name.is_tag("w:pStyle") {
+                        self.style = attributes.value("w:val");
+                    } else if name.is_tag("w:outlineLvl") {
+                        self.outline = attributes.value("w:val").parse().ok();
+                    } else if name.is_tag("w:sectPr") {
+                        self.section_end = true;
+                    } else if name.is_tag("w:t") {
+                        self.in_text = true;
+                    } else if name.is_tag("mc:Fallback") {
+                        self.in_fallback += 1;
+                    } else if (name.is_tag("w:drawing") || name.is_tag("w:pict")) && self.in_fallback == 0 {
+                        self.images += 1;
+                    }
+                    if !self.in_properties && !self.started {
+                        self.started = true;
+                        sink.paragraph_start(self);
+                    }
+                },
+                XmlEvent::CData(ref text) | XmlEvent::Characters(ref text) | XmlEvent::Whitespace(ref text)
+                if self.in_text => {
+                    self.text.push_str(text);
+                    sink.text(text);
+                },
+                XmlEvent::EndElement { ref name } if name.is_tag("w:pPr") => self.in_properties = false,
+                XmlEvent::EndElement { ref name } if name.is_tag("w:t") => self.in_text = false,
+                XmlEvent::EndElement { ref name } if name.is_tag("mc:Fallback") =>
+                    self.in_fallback = self.in_fallback.saturating_sub(1),
+                _ => ()
+            }
+        }
+
+        fn after_forwards(&mut self, children_data: &mut Vec<ParsedData>, _: &mut (dyn Sink + 's)) {
+            for child in children_data.drain(..) {
+                if let ParsedData::Paragraph(paragraph) = child {
+                    self.text.push_str(&paragraph.text);
+                    self.images += paragraph.images;
+                }
+            }
+        }
+    }
+
+    // Table
+    // <w:tbl>
+    //   - row
+    #[derive(Debug, Default)]
+    pub struct Table;
+
+    impl Table {
+        fn is_tag(name: &OwnedName) -> bool { name.is_tag("w:tbl") }
+    }
+
+    impl<'s> ParserState<XmlEvent, ParsedData, dyn Sink + 's> for Table {
+        fn read(&mut self, event: &XmlEvent, sink: &mut (dyn Sink + 's)) {
+            if let XmlEvent::EndElement { ref name } = *event {
+                if Self::is_tag(name) {
+                    sink.table_end();
+                }
+            }
+        }
+
+        fn after_forwards(&mut self, children_data: &mut Vec<ParsedData>, _: &mut (dyn Sink + 's)) {
+            children_data.clear();
+        }
+    }
+
+    // Row
+    // <w:tr>
+    //   - flag if header, started once known
+    //   - cell
+    #[derive(Debug, Default)]
+    pub struct Row {
+        header: bool,
+        started: bool
+    }
+
+    impl Row {
+        fn is_tag(name: &OwnedName) -> bool { name.is_tag("w:tr") }
+
+        fn start(&mut self, sink: &mut dyn Sink) {
+            if !self.started {
+                sink.row_start(self.header);
+                self.started = true;
+            }
+        }
+    }
+
+    impl<'s> ParserState<XmlEvent, ParsedData, dyn Sink + 's> for Row {
+        fn read(&mut self, event: &XmlEvent, sink: &mut (dyn Sink + 's)) {
+            match *event {
+                XmlEvent::StartElement { ref name, ref attributes, .. } => {
+                    if name.is_tag("w:tblHeader") {
+                        self.header = attributes.value("w:val") == "true";
+                    } else if Cell::is_tag(name) {
+                        self.start(sink);
+                        sink.cell_start();
+                    }
+                },
+                XmlEvent::EndElement { ref name } if Self::is_tag(name) => self.start(sink),
+                _ => ()
+            }
+        }
+
+        fn after_forwards(&mut self, children_data: &mut Vec<ParsedData>, _: &mut (dyn Sink + 's)) {
+            children_data.clear();
+        }
+    }
+
+    // Cell
+    // <w:tc>
+    //   - paragraph, one cell each
+    //   - table, its cells joining the row
+    #[derive(Debug, Default)]
+    pub struct Cell;
+
+    impl Cell {
+        fn is_tag(name: &OwnedName) -> bool { name.is_tag("w:tc") }
+    }
+
+    impl<'s> ParserState<XmlEvent, ParsedData, dyn Sink + 's> for Cell {
+        fn read(&mut self, event: &XmlEvent, sink: &mut (dyn Sink + 's)) {
+            if let XmlEvent::EndElement { ref name } = *event {
+                if Self::is_tag(name) {
+                    sink.cell_end();
+                }
+            }
+        }
+
+        fn after_forwards(&mut self, children_data: &mut Vec<ParsedData>, sink: &mut (dyn Sink + 's)) {
+            for child in children_data.drain(..) {
+                if let ParsedData::Paragraph(paragraph) = child {
+                    sink.cell(&paragraph);
+                }
+            }
+        }
+    }
+
+    // Note
+    // <w:footnote w:id="1"> or <w:endnote>, separators having a type
+    //   - paragraph
+    //   - table
+    #[derive(Debug, Default)]
+    pub struct Note;
+
+    impl Note {
+        fn is_tag(name: &OwnedName) -> bool {
+            name.is_tag("w:footnote") || name.is_tag("w:endnote")
+        }
+    }
+
+    impl<'s> ParserState<XmlEvent, ParsedData, dyn Sink + 's> for Note {
+        fn read(&mut self, _: &XmlEvent, _: &mut (dyn Sink + 's)) {
+        }
+
+        fn after_forwards(&mut self, children_data: &mut Vec<ParsedData>, sink: &mut (dyn Sink + 's)) {
+            for child in children_data.drain(..) {
+                if let ParsedData::Paragraph(paragraph) = child {
+                    sink.paragraph(&paragraph);
+                }
+            }
+        }
+    }
+}
+
+
+// Settings of the conversion which do not depend on the output.
+#[derive(Default)]
+pub struct Options {
+    // password of encrypted OOXML packages
+    pub password: Option<String>,
+    // trace of the docx parser states on stderr
+    pub debug: bool,
+    // bounds on the content of untrusted documents
+    pub limits: Limits,
+    // paragraphs and cells kept, by the parse functions and the events
+    // iterator, and their roles
+    pub style_filter: StyleFilter,
+    pub style_map: StyleMap,
+}
+
+pub fn parse<P: AsRef<path::Path>>(filepath: P, options: &Options, reader: &mut dyn WordReader) -> Result<(), MsoError> {
+    let file = fs::File::open(filepath)?;
+    parse_reader(file, options, reader)
+}
+
+pub fn parse_reader<R>(mut source: R, options: &Options, reader: &mut dyn WordReader) -> Result<(), MsoError>
+    where R: io::Read + io::Seek {
+        let mut magic = Vec::new();
+        (&mut source).take(8).read_to_end(&mut magic)?;
+        source.seek(io::SeekFrom::Start(0))?;
+
+        if magic.starts_with(cfb::MAGIC) {
+            parse_compound_file(source, options, reader)
+        } else if magic.starts_with(rtf::MAGIC) {
+            rtf::parse(source, &mut Filtered::new(reader, options, None))
+        } else if flat_xml::is_xml(&magic) {
+            flat_xml::parse(source, options, reader)
+        } else {
+            parse_package(source, options, reader)
+        }
+    }
+
+// Main part of a docx, already read as XML events, for the fuzz targets.
+#[doc(hidden)]
+pub fn parse_docx_events<T>(source: &mut T, options: &Options, reader: &mut dyn WordReader) -> Result<(), MsoError>
+    where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+        let styles = Styles::default();
+        let mut reader = Filtered::new(reader, options, Some(&styles));
+        sax_docx::DocumentRoot::new(options.debug).parse(source, &styles, &mut reader)
+    }
+
+fn parse_package<R>(source: R, options: &Options, reader: &mut dyn WordReader) -> Result<(), MsoError>
+    where R: io::Read + io::Seek {
+        read_package(opc::Package::open(source, &options.limits)?, options, reader)
+    }
+
+fn read_package<R>(mut package: opc::Package<R>, options: &Options, reader: &mut dyn WordReader)
+    -> Result<(), MsoError>
+    where R: io::Read + io::Seek {
+        // OpenDocument packages start with their mime type
+        if package.has_part("/mimetype") {
+            let mut mimetype = String::new();
+            package.part("/mimetype")?.read_to_string(&mut mimetype)?;
+            return match mimetype.trim() {
+                odt::MIMETYPE => odt::parse(&mut package, &options.limits, &mut Filtered::new(reader, options, None)),
+                ods::MIMETYPE => ods::parse(&mut package, &options.limits, &mut Filtered::new(reader, options, None)),
+                other => Err(MsoError::UnsupportedContent(other.to_owned()))
+            };
+        }
+
+        let main_part = package.main_document()?;
+        let styles = parse_styles(&mut package, &main_part)?;
+        let mut reader = Filtered::new(reader, options, Some(&styles));
+        {
+            let mut events = package.xml_part(&main_part)?;
+            sax_docx::DocumentRoot::new(options.debug).parse(&mut events, &styles, &mut reader)?;
+        }
+        parse_notes(&mut package, &main_part, options, &mut reader)
+    }
+
+// Styles related to the main part, if any.
+fn parse_styles<R>(package: &mut opc::Package<R>, main_part: &str) -> Result<Styles, MsoError>
+    where R: io::Read + io::Seek {
+        match opc::styles_part(&package.relationships(main_part)?) {
+            Some(ref part) if package.has_part(part) => Styles::parse(&mut package.xml_part(part)?),
+            _ => Ok(Styles::default())
+        }
+    }
+
+// Footnotes and endnotes related to the main part.
+fn parse_notes<R>(package: &mut opc::Package<R>, main_part: &str, options: &Options, reader: &mut dyn WordReader)
+    -> Result<(), MsoError>
+    where R: io::Read + io::Seek {
+        for relationship in package.relationships(main_part)? {
+            let kind = match note_kind(&relationship) {
+                Some(kind) => kind,
+                None => continue
+            };
+            if relationship.external || !package.has_part(&relationship.target) {
+                continue;
+            }
+
+            let mut events = package.xml_part(&relationship.target)?;
+            sax_docx::DocumentRoot::new(options.debug).parse_notes(&mut events, kind, reader)?;
+        }
+        Ok(())
+    }
+
+fn note_kind(relationship: &opc::Relationship) -> Option<NoteKind> {
+    if relationship.rel_type.ends_with("/footnotes") {
+        Some(NoteKind::Footnote)
+    } else if relationship.rel_type.ends_with("/endnotes") {
+        Some(NoteKind::Endnote)
+    } else {
+        None
+    }
+}
+
+// Compound files are either legacy binary documents, or encrypted OOXML
+// packages.
+fn parse_compound_file<R>(source: R, options: &Options, reader: &mut dyn WordReader) -> Result<(), MsoError>
+    where R: io::Read {
+        let file = cfb::CompoundFile::open(source)?;
+
+        if file.has_entry("EncryptionInfo") && file.has_entry("EncryptedPackage") {
+            match options.password {
+                Some(ref password) => {
+                    let package = encryption::decrypt(&file, password)?;
+                    parse_package(io::Cursor::new(package), options, reader)
+                },
+                None => Err(MsoError::Encrypted)
+            }
+        } else if file.has_entry("WordDocument") {
+            doc::parse(&file, &mut Filtered::new(reader, options, None))
+        } else if file.has_entry("Workbook") || file.has_entry("Book") {
+            Err(MsoError::LegacyFormat("Excel 97-2003 workbook"))
+        } else if file.has_entry("PowerPoint Document") {
+            Err(MsoError::LegacyFormat("PowerPoint 97-2003 presentation"))
+        } else {
+            Err(MsoError::UnsupportedContent("compound file".to_owned()))
+        }
+    }
@@ -0,0 +1,223 @@
+// OpenDocument Spreadsheet
+// A zip container with:
+//   mimetype       application/vnd.oasis.opendocument.spreadsheet
+//   content.xml    the sheets
+// Each sheet is sent as a table, in a section of its own.
+use std::io;
+use std::cmp;
+
+use mso_doc::*;
+
+
+pub const MIMETYPE: &str = "application/vnd.oasis.opendocument.spreadsheet";
+
+// Size of a sheet in LibreOffice and Excel, repetitions are cut beyond.
+const MAX_ROWS: usize = 1_048_576;
+const MAX_COLUMNS: usize = 16_384;
+
+
+pub fn parse<R>(package: &mut opc::Package<R>, limits: &Limits, reader: &mut dyn WordReader) -> Result<(), MsoError>
+    where R: io::Read + io::Seek {
+        let mut root = DocumentRoot { limits: limits.clone(), ..DocumentRoot::default() };
+        root.parse(&mut package.xml_part("/content.xml")?, reader)
+    }
+
+
+#[derive(Default)]
+struct DocumentRoot {
+    limits: Limits,
+    sheet: Sheet,
+    sheets: usize
+}
+
+impl DocumentRoot {
+    fn parse<T>(&mut self, source: &mut T, reader: &mut dyn WordReader) -> Result<(), MsoError>
+        where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+            while let Some(event) = source.next() {
+                if let XmlEvent::StartElement { ref name, .. } = event? {
+                    if Sheet::is_tag(name) {
+                        if self.sheets > 0 {
+                            reader.section_break();
+                        }
+                        self.sheets += 1;
+                        self.sheet.forward(source, &self.limits, reader)?;
+                    }
+                }
+            }
+            Ok(())
+        }
+}
+
+// Sheet
+// <table:table table:name="Sheet1">
+//   - <table:table-header-rows> wrapping header rows
+//   - <table:table-row-group>, <table:table-rows> wrapping rows
+//   - row, possibly repeated
+// Empty rows are only counted, and sent when followed by a non empty one,
+// each counting as a cell against the limits.
+#[derive(Default)]
+struct Sheet {
+    row: SheetRow,
+    empty_rows: usize,
+    sent_rows: usize,
+    // cells sent for all the sheets
+    sent_cells: u64
+}
+
+impl Sheet {
+    fn is_tag(name: &OwnedName) -> bool { name.is_tag("table:table") }
+
+    fn is_header_rows_tag(name: &OwnedName) -> bool { name.is_tag("table:table-header-rows") }
+
+    fn send(&mut self, repeat: usize, header: bool, limits: &Limits, reader: &mut dyn WordReader) -> Result<(), MsoError> {
+        if self.row.cells.is_empty() {
+            self.empty_rows = cmp::min(self.empty_rows.saturating_add(repeat), MAX_ROWS);
+            return Ok(());
+        }
+
+        let empty_rows = cmp::min(self.empty_rows, MAX_ROWS - self.sent_rows);
+        self.sent_cells += empty_rows as u64;
+        limits.check_cells(self.sent_cells)?;
+        for _ in 0..empty_rows {
+            reader.table_new_row();
+        }
+        self.sent_rows += empty_rows;
+        self.empty_rows = 0;
+
+        let repeat = cmp::min(repeat, MAX_ROWS - self.sent_rows);
+        self.sent_cells += (repeat * self.row.cells.len()) as u64;
+        limits.check_cells(self.sent_cells)?;
+        for _ in 0..repeat {
+            reader.table_new_row();
+            for cell in &self.row.cells {
+                reader.table_cell(cell, "", header);
+                reader.table_cell_closed();
+            }
+        }
+        self.sent_rows += repeat;
+        Ok(())
+    }
+
+    fn forward<T>(&mut self, source: &mut T, limits: &Limits, reader: &mut dyn WordReader) -> Result<(), MsoError>
+        where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+            let mut header = false;
+            self.empty_rows = 0;
+            self.sent_rows = 0;
+
+            while let Some(event) = source.next() {
+                match event? {
+                    XmlEvent::StartElement { ref name, ref attributes, .. } => {
+                        if Self::is_tag(name) {
+                            skip_element(source, name)?;
+                        } else if Self::is_header_rows_tag(name) {
+                            header = true;
+                        } else if SheetRow::is_tag(name) {
+                            self.row.forward(source, limits)?;
+                            self.send(repeated(attributes, "table:number-rows-repeated"), header, limits, reader)?;
+                        }
+                    },
+                    XmlEvent::EndElement { ref name, .. } => {
+                        if Self::is_header_rows_tag(name) {
+                            header = false;
+                        } else if Self::is_tag(name) {
+                            reader.table_closed();
+                            break
+                        }
+                    },
+                    _ => ()
+                }
+            }
+            Ok(())
+        }
+}
+
+// SheetRow
+// <table:table-row table:number-rows-repeated="2">
+//   - <table:table-cell table:number-columns-repeated="3" office:value="1.5">
+//       with paragraphs, joined by line breaks
+//   - <table:covered-table-cell> hidden by a merged cell
+// Trailing empty cells are dropped.
+#[derive(Default)]
+struct SheetRow {
+    cells: Vec<String>,
+    empty_cells: usize,
+    paragraph: odt::Paragraph
+}
+
+impl SheetRow {
+    fn is_tag(name: &OwnedName) -> bool { name.is_tag("table:table-row") }
+
+    fn is_cell_tag(name: &OwnedName) -> bool {
+        name.is_tag("table:table-cell") || name.is_tag("table:covered-table-cell")
+    }
+
+    fn push(&mut self, content: String, repeat: usize) {
+        if content.is_empty() {
+            self.empty_cells = cmp::min(self.empty_cells.saturating_add(repeat), MAX_COLUMNS);
+            return;
+        }
+
+        let empty_cells = cmp::min(self.empty_cells, MAX_COLUMNS - self.cells.len());
+        self.cells.extend((0..empty_cells).map(|_| String::new()));
+        self.empty_cells = 0;
+
+        let repeat = cmp::min(repeat, MAX_COLUMNS - self.cells.len());
+        self.cells.extend((0..repeat).map(|_| content.clone()));
+    }
+
+    fn forward<T>(&mut self, source: &mut T, limits: &Limits) -> Result<(), MsoError>
+        where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+            self.cells.clear();
+            self.empty_cells = 0;
+
+            while let Some(event) = source.next() {
+                match event? {
+                    XmlEvent::StartElement { ref name, ref attributes, .. }
+                    if Self::is_cell_tag(name) => {
+                        let content = self.forward_cell(source, name, limits)?;
+                        let content = if content.is_empty() {
+                            attributes.value("office:value")
+                        } else {
+                            content
+                        };
+                        self.push(content, repeated(attributes, "table:number-columns-repeated"));
+                    },
+                    XmlEvent::EndElement { ref name, .. }
+                    if Self::is_tag(name) => break,
+                    _ => ()
+                }
+            }
+            Ok(())
+        }
+
+    fn forward_cell<T>(&mut self, source: &mut T, tag: &OwnedName, limits: &Limits) -> Result<String, MsoError>
+        where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+            let mut content = String::new();
+
+            while let Some(event) = source.next() {
+                match event? {
+                    XmlEvent::StartElement { ref name, ref attributes, .. } => {
+                        if odt::Paragraph::is_tag(name) {
+                            self.paragraph.parse(source, name, attributes, limits)?;
+                            if !content.is_empty() {
+                                content.push('\n');
+                            }
+                            content.push_str(&self.paragraph.text);
+                            self.paragraph.clear();
+                        } else {
+                            // annotations, drawings, nested tables
+                            skip_element(source, name)?;
+                        }
+                    },
+                    XmlEvent::EndElement { ref name, .. }
+                    if name == tag => break,
+                    _ => ()
+                }
+            }
+            Ok(content)
+        }
+}
+
+fn repeated(attributes: &[OwnedAttribute], key: &str) -> usize {
+    attributes.value(key).parse().unwrap_or(1)
+}
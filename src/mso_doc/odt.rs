@@ -0,0 +1,324 @@
+// OpenDocument Text
+// A zip container with:
+//   mimetype       application/vnd.oasis.opendocument.text
+//   styles.xml     common styles, with their display name
+//   content.xml    automatic styles, then the body
+use std::io;
+use std::collections::HashMap;
+
+use mso_doc::*;
+
+
+pub const MIMETYPE: &str = "application/vnd.oasis.opendocument.text";
+
+
+pub fn parse<R>(package: &mut opc::Package<R>, limits: &Limits, reader: &mut dyn WordReader) -> Result<(), MsoError>
+    where R: io::Read + io::Seek {
+        let mut root = DocumentRoot { limits: limits.clone(), ..DocumentRoot::default() };
+
+        if package.has_part("/styles.xml") {
+            root.styles.forward(&mut package.xml_part("/styles.xml")?, false)?;
+        }
+
+        root.parse(&mut package.xml_part("/content.xml")?, reader)
+    }
+
+
+#[derive(Default)]
+struct DocumentRoot {
+    limits: Limits,
+    styles: Styles,
+    paragraph: Paragraph,
+    table: Table
+}
+
+impl DocumentRoot {
+    fn parse<T>(&mut self, source: &mut T, reader: &mut dyn WordReader) -> Result<(), MsoError>
+        where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+            while let Some(event) = source.next() {
+                if let XmlEvent::StartElement { ref name, ref attributes, .. } = event? {
+                    if Styles::is_automatic_tag(name) {
+                        self.styles.forward(source, true)?;
+                    } else if Paragraph::is_tag(name) {
+                        self.paragraph.parse(source, name, attributes, &self.limits)?;
+                        self.send(reader);
+                    } else if Table::is_tag(name) {
+                        self.table.forward(source, &self.styles, &self.limits, reader)?;
+                    }
+                }
+            }
+            Ok(())
+        }
+
+    fn send(&mut self, reader: &mut dyn WordReader) {
+        let style = self.styles.display_name(&self.paragraph.style);
+        match self.paragraph.outline_level {
+            Some(level) => reader.heading_row(&self.paragraph.text, &style, level),
+            None => reader.paragraph_row(&self.paragraph.text, &style)
+        }
+        self.paragraph.send_images(reader);
+        self.paragraph.clear();
+    }
+}
+
+// Styles
+// <office:styles> or <office:automatic-styles>
+//   <style:style style:name="P1" style:parent-style-name="Heading_20_1"/>
+//   <style:style style:name="Heading_20_1" style:display-name="Heading 1"/>
+#[derive(Default)]
+struct Styles {
+    styles: HashMap<String, Style>
+}
+
+struct Style {
+    display_name: String,
+    parent: String,
+    automatic: bool
+}
+
+impl Styles {
+    fn is_automatic_tag(name: &OwnedName) -> bool { name.is_tag("office:automatic-styles") }
+
+    fn is_style_tag(name: &OwnedName) -> bool { name.is_tag("style:style") }
+
+    // Reads the style definitions of a whole styles.xml, or of the
+    // automatic styles section of content.xml.
+    fn forward<T>(&mut self, source: &mut T, automatic: bool) -> Result<(), MsoError>
+        where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+            for event in source {
+                match event? {
+                    XmlEvent::StartElement { ref name, ref attributes, .. }
+                    if Self::is_style_tag(name) => {
+                        self.styles.insert(
+                            attributes.value("style:name"),
+                            Style {
+                                display_name: attributes.value("style:display-name"),
+                                parent: attributes.value("style:parent-style-name"),
+                                automatic
+                            });
+                    },
+                    XmlEvent::EndElement { ref name, .. }
+                    if automatic && Self::is_automatic_tag(name) => break,
+                    _ => ()
+                }
+            }
+            Ok(())
+        }
+
+    // Name of the first common style, skipping automatic styles which only
+    // carry direct formatting.
+    fn display_name(&self, name: &str) -> String {
+        let mut name = name;
+        // bounded walk, in case of a loop in parent styles
+        for _ in 0..self.styles.len() + 1 {
+            match self.styles.get(name) {
+                Some(style) if style.automatic && !style.parent.is_empty() => {
+                    name = &style.parent;
+                },
+                Some(style) if style.automatic => return String::new(),
+                Some(style) if !style.display_name.is_empty() => return style.display_name.clone(),
+                _ => break
+            }
+        }
+        name.to_owned()
+    }
+}
+
+// Paragraph
+// <text:p text:style-name="P1"> or <text:h text:outline-level="1">
+//   - text, possibly in <text:span>
+//   - <text:s text:c="3"/>, <text:tab/>, <text:line-break/>, the repeated
+//     spaces counted in the length of the paragraph
+//   - ignored: <text:note>, <draw:frame> only counted, <office:annotation>
+#[derive(Default)]
+pub struct Paragraph {
+    style: String,
+    outline_level: Option<u32>,
+    pub text: String,
+    images: u32
+}
+
+impl Paragraph {
+    pub fn is_tag(name: &OwnedName) -> bool { name.is_tag("text:p") || name.is_tag("text:h") }
+
+    fn is_ignored_tag(name: &OwnedName) -> bool {
+        name.is_tag("text:note") || name.is_tag("draw:frame") || name.is_tag("office:annotation")
+    }
+
+    pub fn clear(&mut self) {
+        self.style.clear();
+        self.outline_level = None;
+        self.text.clear();
+        self.images = 0;
+    }
+
+    fn send_images(&self, reader: &mut dyn WordReader) {
+        for _ in 0..self.images {
+            reader.image();
+        }
+    }
+
+    pub fn parse<T>(&mut self, source: &mut T, tag: &OwnedName, attributes: &[OwnedAttribute], limits: &Limits)
+        -> Result<(), MsoError>
+        where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+            self.style = attributes.value("text:style-name");
+            if tag.is_tag("text:h") {
+                self.outline_level = Some(attributes.value("text:outline-level").parse().unwrap_or(1));
+            }
+
+            while let Some(event) = source.next() {
+                match event? {
+                    XmlEvent::StartElement { ref name, ref attributes, .. } => {
+                        if Self::is_ignored_tag(name) || Self::is_tag(name) {
+                            if name.is_tag("draw:frame") {
+                                self.images += 1;
+                            }
+                            skip_element(source, name)?;
+                        } else if name.is_tag("text:s") {
+                            let count: usize = attributes.value("text:c").parse().unwrap_or(1);
+                            // one past the limit is enough to exceed it
+                            let count = count.min(limits.max_paragraph_length.saturating_sub(self.text.len()) + 1);
+                            self.text.extend((0..count).map(|_| ' '));
+                        } else if name.is_tag("text:tab") {
+                            self.text.push('\t');
+                        } else if name.is_tag("text:line-break") {
+                            self.text.push('\n');
+                        }
+                    },
+                    XmlEvent::CData(ref cdata) => self.text.push_str(cdata),
+                    XmlEvent::Characters(ref chars) => self.text.push_str(chars),
+                    XmlEvent::Whitespace(ref whsp) => self.text.push_str(whsp),
+                    XmlEvent::EndElement { ref name, .. }
+                    if name == tag => break,
+                    _ => ()
+                }
+                limits.check_paragraph_length(self.text.len())?;
+            }
+            Ok(())
+        }
+}
+
+// Table
+// <table:table>
+//   - <table:table-header-rows> wrapping header rows
+//   - row
+#[derive(Default)]
+struct Table {
+    row: TableRow
+}
+
+impl Table {
+    fn is_tag(name: &OwnedName) -> bool { name.is_tag("table:table") }
+
+    fn is_header_rows_tag(name: &OwnedName) -> bool { name.is_tag("table:table-header-rows") }
+
+    fn forward<T>(&mut self, source: &mut T, styles: &Styles, limits: &Limits, reader: &mut dyn WordReader)
+        -> Result<(), MsoError>
+        where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+            let mut header = false;
+
+            while let Some(event) = source.next() {
+                match event? {
+                    XmlEvent::StartElement { ref name, .. } => {
+                        if Self::is_header_rows_tag(name) {
+                            header = true;
+                        } else if TableRow::is_tag(name) {
+                            reader.table_new_row();
+                            self.row.forward(source, header, styles, limits, reader)?;
+                        }
+                    },
+                    XmlEvent::EndElement { ref name, .. } => {
+                        if Self::is_header_rows_tag(name) {
+                            header = false;
+                        } else if Self::is_tag(name) {
+                            reader.table_closed();
+                            break
+                        }
+                    },
+                    _ => ()
+                }
+            }
+            Ok(())
+        }
+}
+
+// TableRow
+// <table:table-row>
+//   - <table:table-cell> with paragraphs, one event per paragraph, then
+//     its end
+//   - <table:covered-table-cell> hidden by a merged cell
+#[derive(Default)]
+struct TableRow {
+    cell: Paragraph
+}
+
+impl TableRow {
+    fn is_tag(name: &OwnedName) -> bool { name.is_tag("table:table-row") }
+
+    fn is_cell_tag(name: &OwnedName) -> bool { name.is_tag("table:table-cell") }
+
+    fn send(&mut self, header: bool, styles: &Styles, reader: &mut dyn WordReader) {
+        reader.table_cell(
+            &self.cell.text,
+            &styles.display_name(&self.cell.style),
+            header);
+        self.cell.send_images(reader);
+
+        // clear for the cell
+        self.cell.clear();
+    }
+
+    fn forward<T>(&mut self, source: &mut T, header: bool, styles: &Styles, limits: &Limits, reader: &mut dyn WordReader)
+        -> Result<(), MsoError>
+        where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+            while let Some(event) = source.next() {
+                match event? {
+                    XmlEvent::StartElement { ref name, .. }
+                    if Self::is_cell_tag(name) => {
+                        self.forward_cell(source, header, styles, limits, reader)?;
+                    },
+                    XmlEvent::EndElement { ref name, .. }
+                    if Self::is_tag(name) => break,
+                    _ => ()
+                }
+            }
+            Ok(())
+        }
+
+    // Paragraphs of nested tables are flattened into the cell.
+    fn forward_cell<T>(&mut self, source: &mut T, header: bool, styles: &Styles, limits: &Limits, reader: &mut dyn WordReader)
+        -> Result<(), MsoError>
+        where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+            let mut depth = 0;
+            let mut empty = true;
+
+            while let Some(event) = source.next() {
+                match event? {
+                    XmlEvent::StartElement { ref name, ref attributes, .. } => {
+                        if Self::is_cell_tag(name) {
+                            depth += 1;
+                        } else if Paragraph::is_tag(name) {
+                            self.cell.parse(source, name, attributes, limits)?;
+                            self.send(header, styles, reader);
+                            empty = false;
+                        }
+                    },
+                    XmlEvent::EndElement { ref name, .. }
+                    if Self::is_cell_tag(name) => {
+                        if depth == 0 {
+                            break
+                        }
+                        depth -= 1;
+                    },
+                    _ => ()
+                }
+            }
+
+            // keep the columns aligned
+            if empty {
+                self.send(header, styles, reader);
+            }
+            reader.table_cell_closed();
+            Ok(())
+        }
+}
@@ -0,0 +1,300 @@
+// Open Packaging Conventions
+// A zip container holding parts (named by an absolute path), typed by
+// [Content_Types].xml and linked to each other by relationship parts:
+//   /_rels/.rels                     package relationships
+//   /word/_rels/document.xml.rels    relationships of /word/document.xml
+extern crate flate2;
+
+use std::io;
+use std::io::Read;
+use std::collections::HashMap;
+
+use mso_doc::*;
+
+
+const RT_OFFICE_DOCUMENT: [&str; 2] = [
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument",
+    "http://purl.oclc.org/ooxml/officeDocument/relationships/officeDocument"
+];
+
+const RT_EXTENDED_PROPERTIES: [&str; 2] = [
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships/extended-properties",
+    "http://purl.oclc.org/ooxml/officeDocument/relationships/extendedProperties"
+];
+
+const CT_WORD_MAIN: [&str; 4] = [
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml",
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.template.main+xml",
+    "application/vnd.ms-word.document.macroEnabled.main+xml",
+    "application/vnd.ms-word.template.macroEnabledTemplate.main+xml"
+];
+
+// Used when a package comes without any package relationship.
+const DEFAULT_MAIN_PART: &str = "/word/document.xml";
+
+
+// Relationship
+// <Relationship Id="rId1" Type="uri" Target="word/document.xml" TargetMode="Internal"/>
+// Internal targets are resolved to absolute part names.
+pub struct Relationship {
+    pub rel_type: String,
+    pub target: String,
+    pub external: bool
+}
+
+impl Relationship {
+    fn is_tag(name: &OwnedName) -> bool { name.is_tag("Relationship") }
+}
+
+pub fn read_relationships<T>(source_part: &str, source: &mut T) -> Result<Vec<Relationship>, MsoError>
+    where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+        let mut relationships = Vec::new();
+
+        for event in source {
+            match event? {
+                XmlEvent::StartElement { ref name, ref attributes, .. }
+                if Relationship::is_tag(name) => {
+                    let external = attributes.value("TargetMode") == "External";
+                    let target = attributes.value("Target");
+                    relationships.push(Relationship {
+                        rel_type: attributes.value("Type"),
+                        target: if external { target } else { resolve(source_part, &target) },
+                        external
+                    });
+                },
+                XmlEvent::EndElement { ref name, .. }
+                if name.is_tag("Relationships") => break,
+                _ => ()
+            }
+        }
+
+        Ok(relationships)
+    }
+
+// Name of the part holding the relationships of the given source part,
+// the package itself being "/".
+pub fn relationships_part(source_part: &str) -> String {
+    match source_part.rfind('/') {
+        Some(i) => {
+            let (dir, file) = source_part.split_at(i + 1);
+            format!("{}_rels/{}.rels", dir, file)
+        },
+        None => format!("/_rels/{}.rels", source_part)
+    }
+}
+
+// Absolute part name of a relative target, seen from the source part.
+pub fn resolve(source_part: &str, target: &str) -> String {
+    let base = if target.starts_with('/') {
+        ""
+    } else {
+        source_part.rfind('/').map(|i| &source_part[..i]).unwrap_or("")
+    };
+
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in base.split('/').chain(target.split('/')) {
+        match segment {
+            "" | "." => (),
+            ".." => { segments.pop(); },
+            _ => segments.push(segment)
+        }
+    }
+
+    format!("/{}", segments.join("/"))
+}
+
+pub fn is_office_document(relationship: &Relationship) -> bool {
+    !relationship.external && RT_OFFICE_DOCUMENT.contains(&relationship.rel_type.as_str())
+}
+
+// Target of the officeDocument relationship among the package relationships.
+pub fn main_part(package_relationships: &[Relationship]) -> String {
+    package_relationships.iter()
+        .find(|relationship| is_office_document(relationship))
+        .map(|relationship| relationship.target.clone())
+        .unwrap_or_else(|| DEFAULT_MAIN_PART.to_owned())
+}
+
+// Target of the extended properties relationship among the package
+// relationships.
+pub fn extended_properties_part(package_relationships: &[Relationship]) -> Option<String> {
+    package_relationships.iter()
+        .find(|relationship| !relationship.external
+              && RT_EXTENDED_PROPERTIES.contains(&relationship.rel_type.as_str()))
+        .map(|relationship| relationship.target.clone())
+}
+
+// Target of the styles relationship among the relationships of the main part.
+pub fn styles_part(main_part_relationships: &[Relationship]) -> Option<String> {
+    main_part_relationships.iter()
+        .find(|relationship| !relationship.external && relationship.rel_type.ends_with("/styles"))
+        .map(|relationship| relationship.target.clone())
+}
+
+// Only Word documents are understood, if the type of their main part is known.
+pub fn check_main_content_type(content_type: Option<&str>) -> Result<(), MsoError> {
+    match content_type {
+        Some(content_type) if !CT_WORD_MAIN.contains(&content_type) =>
+            Err(MsoError::UnsupportedContent(content_type.to_owned())),
+        _ => Ok(())
+    }
+}
+
+
+// ContentTypes
+// <Types>
+//   <Default Extension="xml" ContentType="..."/>
+//   <Override PartName="/word/document.xml" ContentType="..."/>
+#[derive(Default)]
+struct ContentTypes {
+    defaults: HashMap<String, String>,
+    overrides: HashMap<String, String>
+}
+
+impl ContentTypes {
+    fn parse<T>(&mut self, source: &mut T) -> Result<(), MsoError>
+        where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+            for event in source {
+                if let XmlEvent::StartElement { ref name, ref attributes, .. } = event? {
+                    if name.is_tag("Default") {
+                        self.defaults.insert(
+                            attributes.value("Extension").to_lowercase(),
+                            attributes.value("ContentType"));
+                    } else if name.is_tag("Override") {
+                        self.overrides.insert(
+                            attributes.value("PartName").to_lowercase(),
+                            attributes.value("ContentType"));
+                    }
+                }
+            }
+            Ok(())
+        }
+
+    fn get(&self, part: &str) -> Option<&str> {
+        let part = part.to_lowercase();
+        self.overrides.get(&part)
+            .or_else(|| {
+                part.rfind('.')
+                    .and_then(|i| self.defaults.get(&part[i + 1..]))
+            })
+            .map(|content_type| content_type.as_str())
+    }
+}
+
+
+pub struct Package<R: io::Read + io::Seek> {
+    archive: zip::ZipArchive<R>,
+    // lowercase part name -> index in the archive
+    parts: HashMap<String, usize>,
+    content_types: ContentTypes,
+    limits: Limits
+}
+
+impl<R: io::Read + io::Seek> Package<R> {
+    pub fn open(source: R, limits: &Limits) -> Result<Package<R>, MsoError> {
+        let mut archive = zip::ZipArchive::new(source)?;
+        limits.check_parts(archive.len())?;
+
+        let mut parts = HashMap::new();
+        for i in 0..archive.len() {
+            let name = resolve("/", archive.by_index(i)?.name());
+            parts.insert(name.to_lowercase(), i);
+        }
+
+        let mut package = Package {
+            archive,
+            parts,
+            content_types: ContentTypes::default(),
+            limits: limits.clone()
+        };
+
+        if package.has_part("/[Content_Types].xml") {
+            let mut content_types = ContentTypes::default();
+            content_types.parse(&mut package.xml_part("/[Content_Types].xml")?)?;
+            package.content_types = content_types;
+        }
+
+        Ok(package)
+    }
+
+    pub fn has_part(&self, name: &str) -> bool {
+        self.parts.contains_key(&name.to_lowercase())
+    }
+
+    // Content of a part, failing past the limits of the package.
+    pub fn part(&mut self, name: &str) -> Result<limits::PartReader<zip::read::ZipFile<'_>>, MsoError> {
+        match self.parts.get(&name.to_lowercase()) {
+            Some(&i) => {
+                let file = self.archive.by_index(i)?;
+                let compressed_size = file.compressed_size();
+                Ok(limits::PartReader::new(file, name, compressed_size, &self.limits))
+            },
+            None => Err(MsoError::MissingPart(name.to_owned()))
+        }
+    }
+
+    // Content of a part read from the source of the package, owned by the
+    // reader and so decompressed as it is read. Parts compressed other than
+    // by deflate are rare, and read whole.
+    pub fn into_part<'a>(mut self, name: &str) -> Result<limits::PartReader<Box<dyn io::Read + 'a>>, MsoError>
+        where R: 'a {
+            let i = match self.parts.get(&name.to_lowercase()) {
+                Some(&i) => i,
+                None => return Err(MsoError::MissingPart(name.to_owned()))
+            };
+            let (data_start, compressed_size, compression) = {
+                let file = self.archive.by_index(i)?;
+                (file.data_start(), file.compressed_size(), file.compression())
+            };
+
+            let source: Box<dyn io::Read + 'a> = match compression {
+                zip::CompressionMethod::Stored | zip::CompressionMethod::Deflated => {
+                    let mut source = self.archive.into_inner();
+                    source.seek(io::SeekFrom::Start(data_start))?;
+                    let data = source.take(compressed_size);
+                    if compression == zip::CompressionMethod::Stored {
+                        Box::new(data)
+                    } else {
+                        Box::new(flate2::read::DeflateDecoder::new(data))
+                    }
+                },
+                _ => {
+                    let mut content = Vec::new();
+                    self.part(name)?.read_to_end(&mut content)?;
+                    Box::new(io::Cursor::new(content))
+                }
+            };
+            Ok(limits::PartReader::new(source, name, compressed_size, &self.limits))
+        }
+
+    pub fn xml_part(&mut self, name: &str) -> Result<limits::XmlPart<zip::read::ZipFile<'_>>, MsoError> {
+        Ok(limits::XmlPart::new(self.part(name)?))
+    }
+
+    pub fn content_type(&self, name: &str) -> Option<&str> {
+        self.content_types.get(name)
+    }
+
+    // Relationships of a part, empty when it has none.
+    pub fn relationships(&mut self, source_part: &str) -> Result<Vec<Relationship>, MsoError> {
+        let rels_part = relationships_part(source_part);
+        if !self.has_part(&rels_part) {
+            return Ok(Vec::new());
+        }
+
+        read_relationships(source_part, &mut self.xml_part(&rels_part)?)
+    }
+
+    // Part name of the main document, targeted by the officeDocument
+    // relationship of the package.
+    pub fn main_document(&mut self) -> Result<String, MsoError> {
+        let main_part = main_part(&self.relationships("/")?);
+
+        if !self.has_part(&main_part) {
+            return Err(MsoError::MissingPart(main_part));
+        }
+
+        check_main_content_type(self.content_type(&main_part))?;
+        Ok(main_part)
+    }
+}
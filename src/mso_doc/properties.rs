@@ -0,0 +1,85 @@
+// Extended properties
+// docProps/app.xml of an OOXML package, with the counts of the document
+// as Word saved them:
+//   <Properties>
+//     <Pages>1</Pages>
+//     <Words>12</Words>
+//     <Characters>70</Characters>                 without spaces
+//     <CharactersWithSpaces>81</CharactersWithSpaces>
+//     <Paragraphs>2</Paragraphs>
+use std::io;
+
+use mso_doc::*;
+
+
+#[derive(Debug, Default, serde::Serialize)]
+pub struct SavedCounts {
+    pub pages: Option<u64>,
+    pub words: Option<u64>,
+    pub characters: Option<u64>,
+    pub characters_without_spaces: Option<u64>,
+    pub paragraphs: Option<u64>,
+    pub lines: Option<u64>
+}
+
+impl SavedCounts {
+    fn parse<T>(source: &mut T) -> Result<SavedCounts, MsoError>
+        where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+            let mut counts = SavedCounts::default();
+            let mut element = String::new();
+
+            for event in source {
+                match event? {
+                    XmlEvent::StartElement { ref name, .. } => element = name.local_name.clone(),
+                    XmlEvent::Characters(ref text) => {
+                        let count = text.trim().parse().ok();
+                        match element.as_str() {
+                            "Pages" => counts.pages = count,
+                            "Words" => counts.words = count,
+                            "Characters" => counts.characters_without_spaces = count,
+                            "CharactersWithSpaces" => counts.characters = count,
+                            "Paragraphs" => counts.paragraphs = count,
+                            "Lines" => counts.lines = count,
+                            _ => ()
+                        }
+                    },
+                    XmlEvent::EndElement { .. } => element.clear(),
+                    _ => ()
+                }
+            }
+            Ok(counts)
+        }
+}
+
+// Counts saved in an OOXML package, encrypted or not, none for the other
+// formats or the packages without extended properties.
+pub fn saved_counts<R>(mut source: R, options: &Options) -> Result<Option<SavedCounts>, MsoError>
+    where R: io::Read + io::Seek {
+        let mut magic = Vec::new();
+        (&mut source).take(8).read_to_end(&mut magic)?;
+        source.seek(io::SeekFrom::Start(0))?;
+
+        if magic.starts_with(b"PK") {
+            package_counts(opc::Package::open(source, &options.limits)?)
+        } else if magic.starts_with(cfb::MAGIC) {
+            let file = cfb::CompoundFile::open(source)?;
+            match options.password {
+                Some(ref password) if file.has_entry("EncryptedPackage") => {
+                    let package = encryption::decrypt(&file, password)?;
+                    package_counts(opc::Package::open(io::Cursor::new(package), &options.limits)?)
+                },
+                _ => Ok(None)
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+fn package_counts<R>(mut package: opc::Package<R>) -> Result<Option<SavedCounts>, MsoError>
+    where R: io::Read + io::Seek {
+        match opc::extended_properties_part(&package.relationships("/")?) {
+            Some(ref part) if package.has_part(part) =>
+                SavedCounts::parse(&mut package.xml_part(part)?).map(Some),
+            _ => Ok(None)
+        }
+    }
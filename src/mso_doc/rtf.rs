@@ -0,0 +1,510 @@
+// Rich Text Format
+// {\rtf1\ansi\ansicpg1252\deff0
+//   {\fonttbl{\f0\fcharset0 Arial;}}
+//   {\stylesheet{\s0 Normal;}{\s1\outlinelevel0 heading 1;}}
+//   \pard\s1 Title\par
+//   \trowd\trhdr\cellx1000\cellx2000 \intbl A\cell B\cell\row
+//   \pard Text\par}
+extern crate encoding_rs;
+
+use std::io;
+use std::collections::HashMap;
+
+use self::encoding_rs::Encoding;
+
+use mso_doc::*;
+
+
+pub const MAGIC: &[u8] = b"{\\rtf";
+
+// Destinations holding no text of the document.
+const IGNORED_DESTINATIONS: [&str; 23] = [
+    "colortbl", "info", "pict", "object", "header", "headerl", "headerr", "headerf",
+    "footer", "footerl", "footerr", "footerf", "footnote", "annotation", "fldinst",
+    "listtable", "listoverridetable", "revtbl", "rsidtbl", "xmlnstbl", "themedata",
+    "colorschememapping", "datastore"
+];
+
+
+pub fn parse<R: io::Read>(mut source: R, reader: &mut dyn WordReader) -> Result<(), MsoError> {
+    let mut content = Vec::new();
+    source.read_to_end(&mut content)?;
+
+    let mut document = Document::default();
+    for token in Tokenizer::new(&content) {
+        document.read(token, reader);
+    }
+    document.close(reader);
+    Ok(())
+}
+
+
+enum Token<'a> {
+    GroupStart,
+    GroupEnd,
+    ControlWord(&'a str, Option<i32>),
+    ControlSymbol(u8),
+    Byte(u8)
+}
+
+// Tokenizer
+// Splits the content in groups, control words and symbols, and bytes of
+// text, \'hh escapes being given as bytes.
+struct Tokenizer<'a> {
+    content: &'a [u8],
+    position: usize
+}
+
+impl<'a> Tokenizer<'a> {
+    fn new(content: &'a [u8]) -> Tokenizer<'a> {
+        Tokenizer { content, position: 0 }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.content.get(self.position).cloned()
+    }
+
+    fn control(&mut self) -> Option<Token<'a>> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+            self.position += 1;
+        }
+
+        if start == self.position {
+            // control symbol
+            let symbol = self.peek()?;
+            self.position += 1;
+            if symbol == b'\'' {
+                let hex = self.content.get(self.position..self.position + 2)
+                    .and_then(|hex| ::std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                if let Some(byte) = hex {
+                    self.position += 2;
+                    return Some(Token::Byte(byte));
+                }
+            }
+            return Some(Token::ControlSymbol(symbol));
+        }
+
+        // only ASCII letters were read
+        let name = ::std::str::from_utf8(&self.content[start..self.position]).unwrap_or("");
+
+        let param_start = self.position;
+        if self.peek() == Some(b'-') {
+            self.position += 1;
+        }
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+        let param = ::std::str::from_utf8(&self.content[param_start..self.position]).ok()
+            .and_then(|param| param.parse::<i64>().ok())
+            .map(|param| param.clamp(i32::MIN as i64, i32::MAX as i64) as i32);
+        if param.is_none() {
+            self.position = param_start;
+        }
+
+        // the delimiting space belongs to the control word
+        if self.peek() == Some(b' ') {
+            self.position += 1;
+        }
+
+        // binary data, skipped as a whole
+        if name == "bin" {
+            let length = param.unwrap_or(0).max(0) as usize;
+            self.position = self.position.saturating_add(length).min(self.content.len());
+        }
+
+        Some(Token::ControlWord(name, param))
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        loop {
+            let c = self.peek()?;
+            self.position += 1;
+            match c {
+                b'{' => return Some(Token::GroupStart),
+                b'}' => return Some(Token::GroupEnd),
+                b'\\' => return self.control(),
+                // line breaks of the file are not part of the text
+                b'\r' | b'\n' => (),
+                _ => return Some(Token::Byte(c))
+            }
+        }
+    }
+}
+
+
+#[derive(Clone, Copy, PartialEq)]
+enum Destination {
+    Text,
+    FontTable,
+    StyleSheet,
+    Ignored
+}
+
+// State saved and restored with each group: destination, character and
+// paragraph properties.
+#[derive(Clone)]
+struct GroupState {
+    destination: Destination,
+    unicode_skip: usize,
+    font: Option<i32>,
+    style: Option<i32>,
+    outline_level: Option<u32>,
+    in_table: bool
+}
+
+impl Default for GroupState {
+    fn default() -> GroupState {
+        GroupState {
+            destination: Destination::Text,
+            unicode_skip: 1,
+            font: None,
+            style: None,
+            outline_level: None,
+            in_table: false
+        }
+    }
+}
+
+#[derive(Default)]
+struct Style {
+    name: String,
+    outline_level: Option<u32>
+}
+
+#[derive(Default)]
+struct Document {
+    groups: Vec<GroupState>,
+    state: GroupState,
+    // first control word of the group, telling its destination
+    group_start: bool,
+
+    codepage: Option<i32>,
+    default_font: Option<i32>,
+    font_codepages: HashMap<i32, i32>,
+    styles: HashMap<i32, Style>,
+    // font table or style sheet entry being read, and the depth of its
+    // group
+    entry: Style,
+    entry_id: Option<i32>,
+    entry_depth: Option<usize>,
+
+    bytes: Vec<u8>,
+    // fallback characters left to skip after a \uN
+    skip: usize,
+    high_surrogate: Option<u16>,
+    text: String,
+
+    table_started: bool,
+    row_started: bool,
+    header_row: bool
+}
+
+impl Document {
+    fn read(&mut self, token: Token, reader: &mut dyn WordReader) {
+        let group_start = self.group_start;
+        self.group_start = false;
+
+        match token {
+            Token::GroupStart => {
+                self.flush_bytes();
+                self.groups.push(self.state.clone());
+                if self.entry_depth.is_none() && self.in_entries() {
+                    self.entry_depth = Some(self.groups.len());
+                }
+                self.group_start = true;
+                self.skip = 0;
+            },
+            Token::GroupEnd => {
+                self.flush_bytes();
+                // groups nested in an entry leave it open
+                if self.entry_depth.is_none_or(|depth| depth == self.groups.len()) {
+                    self.end_entry();
+                }
+                if let Some(state) = self.groups.pop() {
+                    self.state = state;
+                }
+                self.skip = 0;
+            },
+            Token::Byte(byte) => {
+                if self.skip > 0 {
+                    self.skip -= 1;
+                } else {
+                    self.bytes.push(byte);
+                }
+            },
+            Token::ControlSymbol(symbol) => {
+                self.flush_bytes();
+                if self.skip > 0 {
+                    self.skip -= 1;
+                    return;
+                }
+                match symbol {
+                    b'*' => self.state.destination = Destination::Ignored,
+                    b'\\' | b'{' | b'}' => self.push_char(symbol as char),
+                    b'~' => self.push_char('\u{a0}'),
+                    b'_' => self.push_char('\u{2011}'),
+                    b'\r' | b'\n' => self.end_paragraph(reader),
+                    _ => ()
+                }
+            },
+            Token::ControlWord(name, param) => {
+                self.flush_bytes();
+                self.control_word(name, param, group_start, reader);
+            }
+        }
+    }
+
+    fn control_word(&mut self, name: &str, param: Option<i32>, group_start: bool, reader: &mut dyn WordReader) {
+        if group_start && IGNORED_DESTINATIONS.contains(&name) {
+            self.state.destination = Destination::Ignored;
+            return;
+        }
+
+        match name {
+            // document
+            "ansicpg" => self.codepage = param,
+            "deff" => self.default_font = param,
+            "fonttbl" => self.state.destination = Destination::FontTable,
+            "stylesheet" => self.state.destination = Destination::StyleSheet,
+            "fcharset" => {
+                if let (Some(font), Some(codepage)) = (self.entry_id, param.and_then(charset_codepage)) {
+                    self.font_codepages.insert(font, codepage);
+                }
+            },
+            "f" if self.state.destination == Destination::FontTable => self.entry_id = param,
+            "s" if self.state.destination == Destination::StyleSheet => self.entry_id = param,
+            "cs" | "ds" | "ts" if self.state.destination == Destination::StyleSheet => {
+                // character, section and table styles are not referenced by paragraphs
+                self.entry_id = None;
+                self.state.destination = Destination::Ignored;
+            },
+            "outlinelevel" if self.state.destination == Destination::StyleSheet => {
+                self.entry.outline_level = outline_level(param);
+            },
+
+            // character properties
+            "f" => self.state.font = param,
+            "plain" => self.state.font = None,
+            "uc" => self.state.unicode_skip = param.unwrap_or(1).max(0) as usize,
+            "u" => {
+                let code = param.unwrap_or(0);
+                let code = if code < 0 { code + 0x10000 } else { code };
+                self.push_unicode(code as u16);
+                self.skip = self.state.unicode_skip;
+            },
+
+            // paragraph properties
+            "pard" => {
+                self.state.style = None;
+                self.state.outline_level = None;
+                self.state.in_table = false;
+            },
+            "s" => self.state.style = param,
+            "outlinelevel" => self.state.outline_level = outline_level(param),
+            "intbl" => self.state.in_table = true,
+
+            // text
+            "par" => self.end_paragraph(reader),
+            "sect" => self.end_section(reader),
+            "tab" => self.push_char('\t'),
+            "line" => self.push_char('\n'),
+            "emdash" => self.push_char('\u{2014}'),
+            "endash" => self.push_char('\u{2013}'),
+            "lquote" => self.push_char('\u{2018}'),
+            "rquote" => self.push_char('\u{2019}'),
+            "ldblquote" => self.push_char('\u{201c}'),
+            "rdblquote" => self.push_char('\u{201d}'),
+            "bullet" => self.push_char('\u{2022}'),
+
+            // tables
+            "trowd" => self.header_row = false,
+            "trhdr" => self.header_row = true,
+            "cell" | "nestcell" => self.end_cell(reader),
+            "row" => self.row_started = false,
+
+            _ => ()
+        }
+    }
+
+    fn codepage(&self) -> i32 {
+        self.state.font.or(self.default_font)
+            .and_then(|font| self.font_codepages.get(&font).cloned())
+            .or(self.codepage)
+            .unwrap_or(1252)
+    }
+
+    fn flush_bytes(&mut self) {
+        if self.bytes.is_empty() {
+            return;
+        }
+        let (text, _, _) = codepage_encoding(self.codepage()).decode(&self.bytes);
+        let text = text.into_owned();
+        self.bytes.clear();
+        for c in text.chars() {
+            self.push_char(c);
+        }
+    }
+
+    fn push_unicode(&mut self, code: u16) {
+        match (self.high_surrogate.take(), code) {
+            (_, 0xD800..=0xDBFF) => self.high_surrogate = Some(code),
+            (Some(high), 0xDC00..=0xDFFF) => {
+                let c = 0x10000 + ((high as u32 - 0xD800) << 10) + (code as u32 - 0xDC00);
+                self.push_char(::std::char::from_u32(c).unwrap_or('\u{fffd}'));
+            },
+            (_, code) => self.push_char(::std::char::from_u32(code as u32).unwrap_or('\u{fffd}'))
+        }
+    }
+
+    fn push_char(&mut self, c: char) {
+        match self.state.destination {
+            Destination::Text => self.text.push(c),
+            Destination::FontTable | Destination::StyleSheet => self.entry.name.push(c),
+            Destination::Ignored => ()
+        }
+    }
+
+    fn in_entries(&self) -> bool {
+        self.state.destination == Destination::FontTable || self.state.destination == Destination::StyleSheet
+    }
+
+    // Entries of the style sheet are groups, the default style may come
+    // without its number.
+    fn end_entry(&mut self) {
+        let name = self.entry.name.trim().trim_end_matches(';').to_owned();
+        if self.state.destination == Destination::StyleSheet && !name.is_empty() {
+            let id = self.entry_id.unwrap_or(0);
+            self.styles.insert(id, Style { name, outline_level: self.entry.outline_level });
+        }
+        self.entry = Style::default();
+        self.entry_id = None;
+        self.entry_depth = None;
+    }
+
+    fn style(&self) -> (String, Option<u32>) {
+        let style = self.state.style.and_then(|id| self.styles.get(&id));
+        let name = style.map(|style| style.name.clone()).unwrap_or_default();
+        let outline_level = self.state.outline_level
+            .or_else(|| style.and_then(|style| style.outline_level));
+        (name, outline_level)
+    }
+
+    fn end_paragraph(&mut self, reader: &mut dyn WordReader) {
+        if self.state.destination != Destination::Text {
+            return;
+        }
+
+        if self.state.in_table {
+            self.end_cell(reader);
+            return;
+        }
+
+        if self.table_started {
+            reader.table_closed();
+            self.table_started = false;
+            self.row_started = false;
+        }
+
+        let (style, outline_level) = self.style();
+        match outline_level {
+            Some(level) => reader.heading_row(&self.text, &style, level),
+            None => reader.paragraph_row(&self.text, &style)
+        }
+        self.text.clear();
+    }
+
+    // Ends the pending paragraph or table, if any, before the section.
+    fn end_section(&mut self, reader: &mut dyn WordReader) {
+        if self.state.destination != Destination::Text {
+            return;
+        }
+
+        if !self.text.is_empty() {
+            self.end_paragraph(reader);
+        }
+        if self.table_started {
+            reader.table_closed();
+            self.table_started = false;
+            self.row_started = false;
+        }
+        reader.section_break();
+    }
+
+    fn end_cell(&mut self, reader: &mut dyn WordReader) {
+        if self.state.destination != Destination::Text {
+            return;
+        }
+
+        self.table_started = true;
+        if !self.row_started {
+            reader.table_new_row();
+            self.row_started = true;
+        }
+
+        let (style, _) = self.style();
+        reader.table_cell(&self.text, &style, self.header_row);
+        reader.table_cell_closed();
+        self.text.clear();
+    }
+
+    fn close(&mut self, reader: &mut dyn WordReader) {
+        self.flush_bytes();
+        if !self.text.trim().is_empty() {
+            self.state.in_table = false;
+            self.end_paragraph(reader);
+        } else if self.table_started {
+            reader.table_closed();
+            self.table_started = false;
+        }
+    }
+}
+
+// Outline levels of RTF start at 0, 9 being the body text.
+fn outline_level(param: Option<i32>) -> Option<u32> {
+    match param {
+        Some(level @ 0..=8) => Some(level as u32 + 1),
+        _ => None
+    }
+}
+
+fn charset_codepage(charset: i32) -> Option<i32> {
+    match charset {
+        0 => Some(1252),
+        77 => Some(10000),
+        128 => Some(932),
+        129 => Some(949),
+        134 => Some(936),
+        136 => Some(950),
+        161 => Some(1253),
+        162 => Some(1254),
+        163 => Some(1258),
+        177 => Some(1255),
+        178 => Some(1256),
+        186 => Some(1257),
+        204 => Some(1251),
+        222 => Some(874),
+        238 => Some(1250),
+        _ => None
+    }
+}
+
+fn codepage_encoding(codepage: i32) -> &'static Encoding {
+    let label = match codepage {
+        874 | 1250..=1258 => format!("windows-{}", codepage),
+        932 => "shift_jis".to_owned(),
+        936 => "gbk".to_owned(),
+        949 => "euc-kr".to_owned(),
+        950 => "big5".to_owned(),
+        10000 => "macintosh".to_owned(),
+        20866 => "koi8-r".to_owned(),
+        65001 => "utf-8".to_owned(),
+        _ => "windows-1252".to_owned()
+    };
+    Encoding::for_label(label.as_bytes()).unwrap_or(encoding_rs::WINDOWS_1252)
+}
@@ -0,0 +1,147 @@
+// Style filter
+// Paragraphs and table cells kept by their style, its id or its name
+// matching glob patterns, case ignored:
+//   --include-style 'heading*' --exclude-style 'TOC*'
+// A paragraph without style matches only the patterns matching an empty
+// string, as '*'. The cells left out are sent empty, so that the tables
+// keep their columns. The same reader applies the roles of the style map,
+// as do the events of a docx.
+extern crate glob;
+
+use self::glob::{MatchOptions, Pattern};
+
+use mso_doc::*;
+
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: false,
+    require_literal_separator: false,
+    require_literal_leading_dot: false
+};
+
+
+#[derive(Clone, Debug, Default)]
+pub struct StyleFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>
+}
+
+impl StyleFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<StyleFilter, glob::PatternError> {
+        Ok(StyleFilter {
+            include: include.iter().map(|p| Pattern::new(p)).collect::<Result<_, _>>()?,
+            exclude: exclude.iter().map(|p| Pattern::new(p)).collect::<Result<_, _>>()?
+        })
+    }
+
+    // Whether a paragraph of the style, given by its id and its name if
+    // known, is kept.
+    pub fn keeps(&self, style: &str, name: Option<&str>) -> bool {
+        let matches = |pattern: &Pattern| {
+            pattern.matches_with(style, MATCH_OPTIONS)
+                || name.is_some_and(|name| pattern.matches_with(name, MATCH_OPTIONS))
+        };
+        (self.include.is_empty() || self.include.iter().any(matches))
+            && !self.exclude.iter().any(matches)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+}
+
+
+// Whether a paragraph of the style is kept, and its role if mapped, the
+// styles of a Word document giving the names of its style ids.
+pub fn keeps(filter: &StyleFilter, style_map: &StyleMap, styles: Option<&Styles>, style: &str) -> (bool, Option<Role>) {
+    let name = styles.and_then(|styles| styles.name(style));
+    let role = style_map.role(style, name);
+    ((filter.is_empty() || filter.keeps(style, name)) && role != Some(Role::Ignore), role)
+}
+
+// Heading level of a kept paragraph, its role overriding the level of
+// its style, and its role if another one.
+pub fn paragraph_role(role: Option<Role>, level: Option<u32>) -> (Option<u32>, Option<Role>) {
+    match role {
+        Some(Role::Heading(level)) => (Some(level), None),
+        Some(role) => (None, Some(role)),
+        None => (level, None)
+    }
+}
+
+
+// Reader forwarding the paragraphs and cells kept by the filter, with
+// the roles of the style map, the styles of a Word document giving the
+// names of its style ids.
+pub struct Filtered<'a> {
+    reader: &'a mut dyn WordReader,
+    filter: &'a StyleFilter,
+    style_map: &'a StyleMap,
+    styles: Option<&'a Styles>,
+    // whether the last paragraph was kept, for its images
+    kept: bool
+}
+
+impl<'a> Filtered<'a> {
+    pub fn new(reader: &'a mut dyn WordReader, options: &'a Options, styles: Option<&'a Styles>) -> Filtered<'a> {
+        Filtered { reader, filter: &options.style_filter, style_map: &options.style_map, styles, kept: true }
+    }
+
+    fn keeps(&mut self, style: &str) -> (bool, Option<Role>) {
+        let (kept, role) = keeps(self.filter, self.style_map, self.styles, style);
+        self.kept = kept;
+        (kept, role)
+    }
+
+    fn send_paragraph(&mut self, text: &str, style: &str, level: Option<u32>) {
+        match self.keeps(style) {
+            (false, _) => (),
+            (true, role) => match paragraph_role(role, level) {
+                (Some(level), _) => self.reader.heading_row(text, style, level),
+                (None, Some(role)) => self.reader.role_row(text, style, role),
+                (None, None) => self.reader.paragraph_row(text, style)
+            }
+        }
+    }
+}
+
+impl<'a> WordReader for Filtered<'a> {
+    fn paragraph_row(&mut self, text: &str, style: &str) {
+        self.send_paragraph(text, style, None);
+    }
+
+    fn heading_row(&mut self, text: &str, style: &str, level: u32) {
+        self.send_paragraph(text, style, Some(level));
+    }
+
+    fn table_new_row(&mut self) {
+        self.reader.table_new_row();
+    }
+
+    fn table_closed(&mut self) {
+        self.reader.table_closed();
+    }
+
+    fn table_cell(&mut self, text: &str, style: &str, header: bool) {
+        let text = if self.keeps(style).0 { text } else { "" };
+        self.reader.table_cell(text, style, header);
+    }
+
+    fn table_cell_closed(&mut self) {
+        self.reader.table_cell_closed();
+    }
+
+    fn section_break(&mut self) {
+        self.reader.section_break();
+    }
+
+    fn note(&mut self, kind: NoteKind, id: &str, text: &str) {
+        self.reader.note(kind, id, text);
+    }
+
+    fn image(&mut self) {
+        if self.kept {
+            self.reader.image();
+        }
+    }
+}
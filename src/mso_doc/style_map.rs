@@ -0,0 +1,66 @@
+// Style map
+// Roles of the styles of the client templates, by style id or name, case
+// ignored, read from JSON or TOML:
+//   {"Titre1": {"heading": 1}, "H1-Corp": {"heading": 1}, "Citation": "quote", "TOC 1": "ignore"}
+//   Titre1 = { heading = 1 }
+//   Citation = "quote"
+// A paragraph of a heading role is sent as a heading of its level, one of
+// another role with its role, heading style or not, and one of the ignore
+// role is left out, the cells of this role being sent empty.
+extern crate serde_json;
+extern crate toml;
+
+use std::collections::HashMap;
+
+use mso_doc::*;
+
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Heading(u32),
+    List,
+    Quote,
+    Code,
+    Caption,
+    Ignore
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct StyleMap {
+    // lowercase style ids and names
+    roles: HashMap<String, Role>
+}
+
+impl StyleMap {
+    pub fn from_json(content: &str) -> Result<StyleMap, String> {
+        serde_json::from_str(content).map_err(|err| err.to_string()).and_then(StyleMap::new)
+    }
+
+    pub fn from_toml(content: &str) -> Result<StyleMap, String> {
+        toml::from_str(content).map_err(|err| err.to_string()).and_then(StyleMap::new)
+    }
+
+    fn new(roles: HashMap<String, Role>) -> Result<StyleMap, String> {
+        for (style, role) in &roles {
+            if let Role::Heading(level) = *role {
+                if !(1..=9).contains(&level) {
+                    return Err(format!("heading level {} of style '{}' not within 1 and 9", level, style));
+                }
+            }
+        }
+        Ok(StyleMap {
+            roles: roles.into_iter().map(|(style, role)| (style.to_lowercase(), role)).collect()
+        })
+    }
+
+    // Role of the style, given by its id and its name if known, the id
+    // coming first.
+    pub fn role(&self, style: &str, name: Option<&str>) -> Option<Role> {
+        if self.roles.is_empty() {
+            return None;
+        }
+        self.roles.get(&style.to_lowercase()).cloned()
+            .or_else(|| name.and_then(|name| self.roles.get(&name.to_lowercase()).cloned()))
+    }
+}
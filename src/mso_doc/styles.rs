@@ -0,0 +1,118 @@
+// Styles of a Word document
+// styles.xml of a docx, or <w:styles> of a Word XML document:
+//   <w:styles>
+//     <w:style w:type="paragraph" w:styleId="Heading1">
+//       <w:name w:val="heading 1"/>
+//       <w:basedOn w:val="Normal"/>
+//       <w:pPr><w:outlineLvl w:val="0"/></w:pPr>
+// Only the names and outline levels of the paragraph styles are kept, to
+// tell the headings apart.
+use std::collections::HashMap;
+
+use mso_doc::*;
+
+
+// Styles based on each other further than this are taken as a cycle.
+const MAX_BASED_ON: usize = 16;
+
+
+#[derive(Debug, Default)]
+pub struct Styles {
+    styles: HashMap<String, Style>
+}
+
+#[derive(Debug, Default)]
+struct Style {
+    name: String,
+    based_on: String,
+    // raw w:outlineLvl, 9 being the body text
+    outline: Option<u32>
+}
+
+impl Styles {
+    // Reads the styles up to the end of <w:styles>.
+    pub fn parse<T>(source: &mut T) -> Result<Styles, MsoError>
+        where T: Iterator<Item=Result<XmlEvent, xml::reader::Error>> {
+            let mut styles = Styles::default();
+            let mut current: Option<(String, Style)> = None;
+
+            for event in source {
+                match event? {
+                    XmlEvent::StartElement { ref name, ref attributes, .. } => {
+                        if name.is_tag("w:style") {
+                            current = if attributes.value("w:type") == "paragraph" {
+                                Some((attributes.value("w:styleId"), Style::default()))
+                            } else {
+                                None
+                            };
+                        } else if let Some((_, ref mut style)) = current {
+                            if name.is_tag("w:name") {
+                                style.name = attributes.value("w:val");
+                            } else if name.is_tag("w:basedOn") {
+                                style.based_on = attributes.value("w:val");
+                            } else if name.is_tag("w:outlineLvl") {
+                                style.outline = attributes.value("w:val").parse().ok();
+                            }
+                        }
+                    },
+                    XmlEvent::EndElement { ref name } => {
+                        if name.is_tag("w:style") {
+                            if let Some((id, style)) = current.take() {
+                                styles.styles.insert(id, style);
+                            }
+                        } else if name.is_tag("w:styles") {
+                            break;
+                        }
+                    },
+                    _ => ()
+                }
+            }
+            Ok(styles)
+        }
+
+    pub fn name(&self, style_id: &str) -> Option<&str> {
+        self.styles.get(style_id).map(|style| style.name.as_str())
+    }
+
+    // Heading level of a paragraph of the style, its own outline level
+    // coming first.
+    pub fn paragraph_level(&self, style_id: &str, outline: Option<u32>) -> Option<u32> {
+        match outline {
+            Some(outline) => outline_level(outline),
+            None => self.level(style_id)
+        }
+    }
+
+    // Heading level of the style, from its outline level or the ones of
+    // the styles it is based on, else from the built-in "heading N" names.
+    pub fn level(&self, style_id: &str) -> Option<u32> {
+        let mut style_id = style_id;
+        for _ in 0..MAX_BASED_ON {
+            let style = self.styles.get(style_id)?;
+            if let Some(outline) = style.outline {
+                return outline_level(outline);
+            }
+            if let Some(level) = heading_name_level(&style.name) {
+                return Some(level);
+            }
+            style_id = &style.based_on;
+        }
+        None
+    }
+}
+
+// Outline levels start at 0, 9 being the body text.
+fn outline_level(outline: u32) -> Option<u32> {
+    match outline {
+        0..=8 => Some(outline + 1),
+        _ => None
+    }
+}
+
+fn heading_name_level(name: &str) -> Option<u32> {
+    let lowercase = name.to_lowercase();
+    match lowercase.strip_prefix("heading ")?.parse() {
+        Ok(level @ 1..=9) => Some(level),
+        _ => None
+    }
+}
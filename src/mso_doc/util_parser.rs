@@ -0,0 +1,167 @@
+// Declarative parser
+// Stack of states, each reading the events until the dispatcher asks to
+// forward to a child state, if the transitions allow it, or to end:
+//   states       collect their data from the events they read, and from
+//                the data of their children once these are done
+//   phony        states only reading through the events, their children
+//                data going to the closest state
+//   transitions  child states each state may forward to
+//   context      shared by the states, for them to send what they read as
+//                they go rather than once the parse is done
+// The states left when the items run out end without the data of their
+// unfinished children. The parse runs in full, or a state at a time for
+// the callers pulling what the context receives.
+#[derive(Debug, PartialEq)]
+pub enum NextAction {
+    Stay,
+    ForwardOrStay(&'static str),
+    DoneIfStay(&'static str),
+    Done
+}
+
+pub trait ParserState<T, W, C: ?Sized> {
+    fn read(&mut self, element: &T, context: &mut C);
+
+    // Data of the children done since the state last ran, in the order
+    // they completed.
+    fn after_forwards(&mut self, children_data: &mut Vec<W>, context: &mut C);
+}
+
+macro_rules! decl_parser {
+    ( $parser:ident,
+      item: $sitem:ty,
+      dispatch: $dispatcher:ident,
+      data wrapper: $dwrapper:ident,
+      context: $context:ty,
+      states: [ $($state_name:ident as $state_struct:ident),* ],
+      phony: [ $($phony:ident),* ],
+      transitions: $( $from_state:ident => [ $($to_state:ident),* ] ),*) => {
+
+        #[derive(Debug)]
+        pub enum $dwrapper {
+            NoPreviousData,
+            $( $state_struct($state_struct), )*
+        }
+
+        pub struct $parser {
+            // state name, its data, and where its children data start
+            stack: Vec<(&'static str, $dwrapper, usize)>,
+            collected_data: Vec<$dwrapper>,
+            exhausted: bool,
+            pub debug: bool
+        }
+
+        impl $parser {
+            const TRANSITIONS: &'static [(&'static str, &'static str)] = &[
+                $( $( (stringify!($from_state), stringify!($to_state)), )* )*
+            ];
+
+            pub fn new(entry_state_name: &'static str) -> $parser {
+                $parser {
+                    stack: vec![(entry_state_name, $dwrapper::NoPreviousData, 0)],
+                    collected_data: Vec::new(),
+                    exhausted: false,
+                    debug: false
+                }
+            }
+
+            // Data of the states done, the entry state's being the last.
+            pub fn parse(mut self, source: &mut dyn Iterator<Item=$sitem>, context: &mut $context) -> Vec<$dwrapper> {
+                while self.step(source, context) {}
+                self.collected_data
+            }
+
+            // Runs the state on top of the stack up to its next forward or
+            // end, so that the parse can be resumed between two calls, and
+            // tells whether states are left.
+            pub fn step(&mut self, source: &mut dyn Iterator<Item=$sitem>, context: &mut $context) -> bool {
+                let (state_name, data, children_start) = match self.stack.pop() {
+                    Some(state) => state,
+                    None => return false
+                };
+                let (data, action) = self.run(state_name, data, children_start, source, context);
+
+                match action {
+                    NextAction::Stay => self.stack.push((state_name, data, children_start)),
+                    NextAction::ForwardOrStay(next_state_name) => {
+                        self.stack.push((state_name, data, children_start));
+                        if self.allowed_move(state_name, next_state_name) {
+                            self.stack.push((next_state_name, $dwrapper::NoPreviousData, self.collected_data.len()));
+                        }
+                    },
+                    NextAction::DoneIfStay(same_state_name) => {
+                        if same_state_name == state_name {
+                            self.collected_data.push(data);
+                        } else {
+                            self.stack.push((state_name, data, children_start));
+                        }
+                    },
+                    NextAction::Done => self.collected_data.push(data)
+                }
+                !self.stack.is_empty()
+            }
+
+            fn run(&mut self, state_name: &'static str, data: $dwrapper, children_start: usize,
+                   source: &mut dyn Iterator<Item=$sitem>, context: &mut $context) -> ($dwrapper, NextAction) {
+                if self.exhausted {
+                    self.collected_data.truncate(children_start);
+                    return (data, NextAction::Done);
+                }
+                match state_name {
+                    $( stringify!($state_name) => {
+                        let mut state = match data {
+                            $dwrapper::$state_struct(state) => state,
+                            _ => $state_struct::default()
+                        };
+
+                        // what to do after a forward parsing
+                        let start = children_start.min(self.collected_data.len());
+                        let mut children_data = self.collected_data.split_off(start);
+                        state.after_forwards(&mut children_data, context);
+                        self.collected_data.append(&mut children_data);
+
+                        let action = self.read_events(state_name, source, context,
+                                                      |element, context| state.read(element, context));
+                        ($dwrapper::$state_struct(state), action)
+                    }, )*
+                    $( stringify!($phony) => {
+                        let action = self.read_events(state_name, source, context, |_, _| ());
+                        (data, action)
+                    }, )*
+                    _ => (data, NextAction::Done)
+                }
+            }
+
+            fn read_events<F>(&mut self, state_name: &str, source: &mut dyn Iterator<Item=$sitem>,
+                              context: &mut $context, mut read: F) -> NextAction
+                where F: FnMut(&$sitem, &mut $context) {
+                    loop {
+                        let element = match source.next() {
+                            Some(element) => element,
+                            None => {
+                                self.exhausted = true;
+                                return NextAction::Done;
+                            }
+                        };
+                        if self.debug {
+                            eprintln!("In {}, got {:?}", state_name, element);
+                        }
+
+                        read(&element, context);
+                        let action = $dispatcher(&element);
+                        if action != NextAction::Stay {
+                            return action;
+                        }
+                    }
+                }
+
+            fn allowed_move(&self, from_state: &str, to_state: &str) -> bool {
+                let allowed = Self::TRANSITIONS.contains(&(from_state, to_state));
+                if !allowed && self.debug {
+                    eprintln!("Transition not allowed from {} to {}", from_state, to_state);
+                }
+                allowed
+            }
+        }
+    }
+}
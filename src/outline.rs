@@ -0,0 +1,73 @@
+// Outline
+// Table of contents of a document, its headings numbered after their
+// nesting rather than their level, a heading 3 right under a heading 1
+// being numbered as its first child:
+//   1. Contract
+//     1.1. Payment
+//       1.1.1. Terms
+//   2. Annexes
+extern crate serde;
+
+use std::io;
+use std::io::Write;
+
+use self::serde::Serialize;
+
+use document::{Block, Document};
+
+
+#[derive(Debug, Serialize)]
+pub struct Heading {
+    pub level: u32,
+    pub number: String,
+    pub text: String,
+    // index of the heading among the paragraphs of the document
+    pub paragraph: usize
+}
+
+impl Heading {
+    // Nesting of the heading in the outline, from 0.
+    pub fn depth(&self) -> usize {
+        self.number.matches('.').count()
+    }
+}
+
+pub fn headings(document: &Document) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    // levels of the headings enclosing the next one, and their numbers
+    let mut parents: Vec<u32> = Vec::new();
+    let mut numbers: Vec<u32> = Vec::new();
+
+    for (i, block) in document.paragraphs().enumerate() {
+        let level = match *block {
+            Block::Paragraph { level: Some(level), .. } => level,
+            _ => continue
+        };
+
+        while parents.last().is_some_and(|&parent| parent >= level) {
+            parents.pop();
+        }
+        parents.push(level);
+        numbers.truncate(parents.len());
+        if numbers.len() < parents.len() {
+            numbers.push(0);
+        }
+        *numbers.last_mut().unwrap() += 1;
+
+        headings.push(Heading {
+            level,
+            number: numbers.iter().map(|n| n.to_string()).collect::<Vec<_>>().join("."),
+            text: block.text(),
+            paragraph: i
+        });
+    }
+    headings
+}
+
+// One line per heading, indented after its nesting.
+pub fn write_text<W: Write>(headings: &[Heading], out: &mut W) -> io::Result<()> {
+    for heading in headings {
+        writeln!(out, "{}{}. {}", "  ".repeat(heading.depth()), heading.number, heading.text)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use std::io;
+use std::io::Write;
+use std::fmt;
+use std::str::FromStr;
+
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+
+// Destination of a converter, keeping the first write error since the
+// reader callbacks cannot return it.
+pub struct Output<W: Write> {
+    out: W,
+    error: Option<io::Error>
+}
+
+impl<W: Write> Output<W> {
+    pub fn new(out: W) -> Output<W> {
+        Output { out, error: None }
+    }
+
+    pub fn write(&mut self, args: fmt::Arguments) {
+        if self.error.is_none() {
+            if let Err(err) = self.out.write_fmt(args) {
+                self.error = Some(err);
+            }
+        }
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        match self.error.take() {
+            Some(err) => Err(err),
+            None => self.out.flush().map(|_| self.out)
+        }
+    }
+}
+
+
+#[derive(Default, Clone, Copy)]
+pub enum Newline {
+    #[default]
+    Lf,
+    Crlf
+}
+
+impl FromStr for Newline {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Newline, String> {
+        match s {
+            "lf" => Ok(Newline::Lf),
+            "crlf" => Ok(Newline::Crlf),
+            _ => Err(format!("unknown newline '{}'", s))
+        }
+    }
+}
+
+// Writer of a whole output stream, translating the line endings and
+// starting with a byte order mark if asked.
+pub struct Encoder<W: Write> {
+    out: W,
+    newline: Newline,
+    pending_bom: bool,
+    // last byte written, a line ending already in CRLF not doubled even
+    // when split between writes
+    last: Option<u8>
+}
+
+impl<W: Write> Encoder<W> {
+    pub fn new(out: W, newline: Newline, bom: bool) -> Encoder<W> {
+        Encoder { out, newline, pending_bom: bom, last: None }
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.pending_bom {
+            self.out.write_all(UTF8_BOM)?;
+            self.pending_bom = false;
+        }
+
+        match self.newline {
+            Newline::Lf => self.out.write_all(buf)?,
+            Newline::Crlf => {
+                let mut start = 0;
+                for (i, &c) in buf.iter().enumerate() {
+                    let previous = if i > 0 { Some(buf[i - 1]) } else { self.last };
+                    if c == b'\n' && previous != Some(b'\r') {
+                        self.out.write_all(&buf[start..i])?;
+                        self.out.write_all(b"\r")?;
+                        start = i;
+                    }
+                }
+                self.out.write_all(&buf[start..])?;
+            }
+        }
+        if let Some(&c) = buf.last() {
+            self.last = Some(c);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
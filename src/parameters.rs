@@ -1,33 +1,460 @@
 extern crate argparse;
 
+use std::env;
+use std::fs;
+use std::io;
+use std::process;
+use std::thread;
+use std::str::FromStr;
 
-pub struct ArgOptions {
-    pub filename: String
+use mso_to_text::mso_doc::Limits;
+use mso_to_text::output::Newline;
+
+
+#[derive(Default, Clone, Copy)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Csv,
+    Json,
+    Chunks
+}
+
+impl OutputFormat {
+    // Extension of the converted files.
+    pub fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Text => "txt",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Json => "json",
+            OutputFormat::Chunks => "jsonl"
+        }
+    }
 }
 
-impl Default for ArgOptions {
-    fn default() -> ArgOptions {
-        ArgOptions { filename: "".to_owned() }
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<OutputFormat, String> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "csv" => Ok(OutputFormat::Csv),
+            "json" => Ok(OutputFormat::Json),
+            "chunks" => Ok(OutputFormat::Chunks),
+            _ => Err(format!("unknown output format '{}'", s))
+        }
     }
 }
 
 
-pub fn parse() -> ArgOptions {
-    let mut argopt = ArgOptions::default();
+#[derive(Default)]
+pub struct ArgOptions {
+    pub files: Vec<String>,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub include_style: Vec<String>,
+    pub exclude_style: Vec<String>,
+    pub style_map: Option<String>,
+    pub output: Option<String>,
+    pub output_dir: Option<String>,
+    pub newline: Newline,
+    pub bom: bool,
+    pub jobs: usize,
+    pub format: OutputFormat,
+    pub outline: bool,
+    pub stats: bool,
+    pub chunk_size: usize,
+    pub password: Option<String>,
+    pub limits: Limits,
+    pub debug: bool,
+    password_file: Option<String>
+}
+
+
+// Options of the grep subcommand.
+#[derive(Default)]
+pub struct GrepOptions {
+    pub pattern: String,
+    pub files: Vec<String>,
+    pub ignore_case: bool,
+    pub files_with_matches: bool,
+    pub count: bool,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub jobs: usize,
+    pub password: Option<String>,
+    pub limits: Limits,
+    password_file: Option<String>
+}
+
+// Options of the diff subcommand.
+#[derive(Default)]
+pub struct DiffOptions {
+    pub old: String,
+    pub new: String,
+    pub side_by_side: bool,
+    pub width: usize,
+    pub context: usize,
+    pub password: Option<String>,
+    pub limits: Limits,
+    password_file: Option<String>
+}
+
+pub enum Command {
+    Convert(ArgOptions),
+    Grep(GrepOptions),
+    Diff(DiffOptions)
+}
+
+
+// Subcommand named by the first argument, else the conversion.
+pub fn parse() -> Command {
+    let args: Vec<String> = env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("grep") => Command::Grep(parse_grep(args[2..].to_vec())),
+        Some("diff") => Command::Diff(parse_diff(args[2..].to_vec())),
+        _ => Command::Convert(parse_convert())
+    }
+}
+
+fn parse_convert() -> ArgOptions {
+    let mut argopt = ArgOptions {
+        jobs: thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        chunk_size: 2000,
+        ..ArgOptions::default()
+    };
 
     // parsing of program parameters
     {
         let mut prog = argparse::ArgumentParser::new();
-        prog.set_description("Convert MS Office document 'docx' to text.");
-        prog.refer(&mut argopt.filename)
+        prog.set_description("Convert office documents 'docx', 'doc', 'odt', 'ods', 'rtf' and Word XML to text. \
+                              Search them with the 'grep' subcommand, compare two with 'diff'.");
+        prog.refer(&mut argopt.format)
+            .metavar("format")
+            .add_option(
+                &["-f", "--format"],
+                argparse::Store,
+                "Output format: text (default), csv (tables only), json, or chunks \
+                (JSON Lines of the passages under each heading)");
+        prog.refer(&mut argopt.chunk_size)
+            .metavar("chars")
+            .add_option(
+                &["--chunk-size"],
+                argparse::Store,
+                "Maximum characters of a chunk, split between paragraphs (default: 2000)");
+        prog.refer(&mut argopt.outline)
+            .add_option(
+                &["--outline"],
+                argparse::StoreTrue,
+                "Write the numbered headings only, as text or json");
+        prog.refer(&mut argopt.stats)
+            .add_option(
+                &["--stats"],
+                argparse::StoreTrue,
+                "Write the counts of words, characters, paragraphs, tables, notes and images, \
+                as text or json");
+        prog.refer(&mut argopt.output)
+            .metavar("path")
+            .add_option(
+                &["-o", "--output"],
+                argparse::StoreOption,
+                "Write the conversion in this file instead of stdout");
+        prog.refer(&mut argopt.newline)
+            .metavar("newline")
+            .add_option(
+                &["--newline"],
+                argparse::Store,
+                "Line ending: lf (default) or crlf");
+        prog.refer(&mut argopt.bom)
+            .add_option(
+                &["--bom"],
+                argparse::StoreTrue,
+                "Start the output with a UTF-8 byte order mark");
+        prog.refer(&mut argopt.debug)
+            .add_option(
+                &["--debug"],
+                argparse::StoreTrue,
+                "Trace the states of the docx parser on stderr");
+        refer_password(&mut prog, &mut argopt.password, &mut argopt.password_file);
+        prog.refer(&mut argopt.include)
+            .metavar("glob")
+            .add_option(
+                &["--include"],
+                argparse::Collect,
+                "Convert only the files of the directories matching the pattern \
+                (default: known document extensions)");
+        prog.refer(&mut argopt.exclude)
+            .metavar("glob")
+            .add_option(
+                &["--exclude"],
+                argparse::Collect,
+                "Skip the files of the directories matching the pattern");
+        prog.refer(&mut argopt.include_style)
+            .metavar("glob")
+            .add_option(
+                &["--include-style"],
+                argparse::Collect,
+                "Keep only the paragraphs and cells of the styles matching the pattern, \
+                by id or name");
+        prog.refer(&mut argopt.exclude_style)
+            .metavar("glob")
+            .add_option(
+                &["--exclude-style"],
+                argparse::Collect,
+                "Leave out the paragraphs and cells of the styles matching the pattern, \
+                by id or name");
+        prog.refer(&mut argopt.style_map)
+            .metavar("path")
+            .add_option(
+                &["--style-map"],
+                argparse::StoreOption,
+                "JSON or TOML file of the roles of the styles, by id or name: \
+                heading level, list, quote, code, caption or ignore");
+        prog.refer(&mut argopt.output_dir)
+            .metavar("dir")
+            .add_option(
+                &["--output-dir"],
+                argparse::StoreOption,
+                "Write each conversion in this directory, mirroring the input tree");
+        prog.refer(&mut argopt.jobs)
+            .metavar("n")
+            .add_option(
+                &["-j", "--jobs"],
+                argparse::Store,
+                "Number of documents converted in parallel (default: number of CPUs)");
+        refer_limits(&mut prog, &mut argopt.limits);
+        prog.refer(&mut argopt.files)
             .required()
             .metavar("file")
             .add_argument(
                 "file",
-                argparse::Store,
-                "MS Office file");
+                argparse::List,
+                "Office documents, or directories of documents");
         prog.parse_args_or_exit();
     }
 
-    return argopt;
+    if argopt.output.is_some() && argopt.output_dir.is_some() {
+        eprintln!("--output and --output-dir are exclusive");
+        process::exit(2);
+    }
+
+    if argopt.outline && argopt.stats {
+        eprintln!("--outline and --stats are exclusive");
+        process::exit(2);
+    }
+    if (argopt.outline || argopt.stats) && !matches!(argopt.format, OutputFormat::Text | OutputFormat::Json) {
+        eprintln!("--{} is written as text or json", if argopt.outline { "outline" } else { "stats" });
+        process::exit(2);
+    }
+
+    resolve_password(&mut argopt.password, &argopt.password_file);
+
+    argopt
+}
+
+// Flags of the limits on untrusted documents, shared by the subcommands.
+fn refer_limits<'a>(prog: &mut argparse::ArgumentParser<'a>, limits: &'a mut Limits) {
+    prog.refer(&mut limits.max_part_size)
+        .metavar("bytes")
+        .add_option(
+            &["--max-part-size"],
+            argparse::Store,
+            "Fail on a part of a package decompressing to more bytes (default: 256 MiB)");
+    prog.refer(&mut limits.max_compression_ratio)
+        .metavar("ratio")
+        .add_option(
+            &["--max-ratio"],
+            argparse::Store,
+            "Fail on a part decompressing to more times its compressed size (default: 200)");
+    prog.refer(&mut limits.max_parts)
+        .metavar("n")
+        .add_option(
+            &["--max-parts"],
+            argparse::Store,
+            "Fail on a package of more parts (default: 10000)");
+    prog.refer(&mut limits.max_depth)
+        .metavar("n")
+        .add_option(
+            &["--max-depth"],
+            argparse::Store,
+            "Fail on XML elements nested deeper (default: 256)");
+    prog.refer(&mut limits.max_paragraph_length)
+        .metavar("bytes")
+        .add_option(
+            &["--max-paragraph"],
+            argparse::Store,
+            "Fail on a paragraph of more bytes of text (default: 16 MiB)");
+    prog.refer(&mut limits.max_cells)
+        .metavar("n")
+        .add_option(
+            &["--max-cells"],
+            argparse::Store,
+            "Fail on a spreadsheet of more cells, repeated ones included (default: 10000000)");
+}
+
+// Flags of the password of encrypted documents, shared by the subcommands.
+fn refer_password<'a>(prog: &mut argparse::ArgumentParser<'a>, password: &'a mut Option<String>,
+                      password_file: &'a mut Option<String>) {
+    prog.refer(password)
+        .metavar("password")
+        .add_option(
+            &["--password"],
+            argparse::StoreOption,
+            "Password of encrypted documents");
+    prog.refer(password_file)
+        .metavar("path")
+        .add_option(
+            &["--password-file"],
+            argparse::StoreOption,
+            "File holding the password of encrypted documents \
+            (default: $MSOCONV_PASSWORD)");
+}
+
+// Password of the flag, else of the file, else of the environment.
+fn resolve_password(password: &mut Option<String>, password_file: &Option<String>) {
+    if password.is_none() {
+        *password = match *password_file {
+            Some(ref path) => Some(read_password(path)),
+            None => env::var("MSOCONV_PASSWORD").ok()
+        };
+    }
+}
+
+fn parse_grep(args: Vec<String>) -> GrepOptions {
+    let mut argopt = GrepOptions {
+        jobs: thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        ..GrepOptions::default()
+    };
+
+    {
+        let mut prog = argparse::ArgumentParser::new();
+        prog.set_description("Search the paragraphs and table cells of documents for a regular expression.");
+        prog.refer(&mut argopt.ignore_case)
+            .add_option(
+                &["-i", "--ignore-case"],
+                argparse::StoreTrue,
+                "Ignore the case of the letters");
+        prog.refer(&mut argopt.files_with_matches)
+            .add_option(
+                &["-l", "--files-with-matches"],
+                argparse::StoreTrue,
+                "Write only the names of the documents with a match");
+        prog.refer(&mut argopt.count)
+            .add_option(
+                &["-c", "--count"],
+                argparse::StoreTrue,
+                "Write only the number of matching paragraphs and cells of each document");
+        refer_password(&mut prog, &mut argopt.password, &mut argopt.password_file);
+        prog.refer(&mut argopt.include)
+            .metavar("glob")
+            .add_option(
+                &["--include"],
+                argparse::Collect,
+                "Search only the files of the directories matching the pattern \
+                (default: known document extensions)");
+        prog.refer(&mut argopt.exclude)
+            .metavar("glob")
+            .add_option(
+                &["--exclude"],
+                argparse::Collect,
+                "Skip the files of the directories matching the pattern");
+        prog.refer(&mut argopt.jobs)
+            .metavar("n")
+            .add_option(
+                &["-j", "--jobs"],
+                argparse::Store,
+                "Number of documents searched in parallel (default: number of CPUs)");
+        refer_limits(&mut prog, &mut argopt.limits);
+        prog.refer(&mut argopt.pattern)
+            .required()
+            .metavar("pattern")
+            .add_argument(
+                "pattern",
+                argparse::Store,
+                "Regular expression");
+        prog.refer(&mut argopt.files)
+            .required()
+            .metavar("file")
+            .add_argument(
+                "file",
+                argparse::List,
+                "Office documents, or directories of documents");
+
+        let args = [vec!["mso_to_text grep".to_owned()], args].concat();
+        if let Err(code) = prog.parse(args, &mut io::stdout(), &mut io::stderr()) {
+            process::exit(code);
+        }
+    }
+
+    resolve_password(&mut argopt.password, &argopt.password_file);
+
+    argopt
+}
+
+fn parse_diff(args: Vec<String>) -> DiffOptions {
+    let mut argopt = DiffOptions {
+        width: 130,
+        context: 3,
+        ..DiffOptions::default()
+    };
+
+    {
+        let mut prog = argparse::ArgumentParser::new();
+        prog.set_description("Compare the paragraphs and table cells of two documents, \
+                              the changed words of a paragraph marked [-deleted-] and {+inserted+}.");
+        prog.refer(&mut argopt.side_by_side)
+            .add_option(
+                &["-y", "--side-by-side"],
+                argparse::StoreTrue,
+                "Write the documents in two columns");
+        prog.refer(&mut argopt.width)
+            .metavar("n")
+            .add_option(
+                &["-W", "--width"],
+                argparse::Store,
+                "Width of the side by side output (default: 130)");
+        prog.refer(&mut argopt.context)
+            .metavar("n")
+            .add_option(
+                &["-U", "--unified"],
+                argparse::Store,
+                "Number of unchanged paragraphs around the changes (default: 3)");
+        refer_password(&mut prog, &mut argopt.password, &mut argopt.password_file);
+        refer_limits(&mut prog, &mut argopt.limits);
+        prog.refer(&mut argopt.old)
+            .required()
+            .metavar("old")
+            .add_argument(
+                "old",
+                argparse::Store,
+                "Old version of the document");
+        prog.refer(&mut argopt.new)
+            .required()
+            .metavar("new")
+            .add_argument(
+                "new",
+                argparse::Store,
+                "New version of the document");
+
+        let args = [vec!["mso_to_text diff".to_owned()], args].concat();
+        if let Err(code) = prog.parse(args, &mut io::stdout(), &mut io::stderr()) {
+            process::exit(code);
+        }
+    }
+
+    resolve_password(&mut argopt.password, &argopt.password_file);
+
+    argopt
+}
+
+// First line of the file, so that a trailing end of line is not part of
+// the password.
+fn read_password(path: &str) -> String {
+    match fs::read_to_string(path) {
+        Ok(content) => content.lines().next().unwrap_or("").to_owned(),
+        Err(err) => {
+            eprintln!("{}: {}", path, err);
+            process::exit(2);
+        }
+    }
 }
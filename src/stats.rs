@@ -0,0 +1,142 @@
+// Statistics
+// Counts of a document from the reader events, its words found by the
+// Unicode word boundaries (UAX #29), so that a word is any run of letters
+// or digits, and each CJK ideograph a word of its own. Text is counted in
+// the paragraphs and the table cells, not in the notes.
+extern crate serde;
+extern crate unicode_segmentation;
+
+use std::io;
+use std::io::Write;
+
+use self::serde::Serialize;
+use self::unicode_segmentation::UnicodeSegmentation;
+
+use mso_doc;
+use mso_doc::{NoteKind, SavedCounts};
+
+
+#[derive(Debug, Default, Serialize)]
+pub struct Stats {
+    pub words: u64,
+    pub characters: u64,
+    pub characters_without_spaces: u64,
+    // non empty, headings included
+    pub paragraphs: u64,
+    pub headings: u64,
+    pub tables: u64,
+    pub cells: u64,
+    pub footnotes: u64,
+    pub endnotes: u64,
+    pub images: u64
+}
+
+// Counts of the document, and the ones saved in it to compare with.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub counted: Stats,
+    pub saved: Option<SavedCounts>
+}
+
+// One line per count: name, count, and saved count if any.
+pub fn write_text<W: Write>(report: &Report, out: &mut W) -> io::Result<()> {
+    let counted = &report.counted;
+    let saved = report.saved.as_ref();
+    let rows = [
+        ("words", counted.words, saved.and_then(|saved| saved.words)),
+        ("characters", counted.characters, saved.and_then(|saved| saved.characters)),
+        ("characters_without_spaces", counted.characters_without_spaces,
+         saved.and_then(|saved| saved.characters_without_spaces)),
+        ("paragraphs", counted.paragraphs, saved.and_then(|saved| saved.paragraphs)),
+        ("headings", counted.headings, None),
+        ("tables", counted.tables, None),
+        ("cells", counted.cells, None),
+        ("footnotes", counted.footnotes, None),
+        ("endnotes", counted.endnotes, None),
+        ("images", counted.images, None)
+    ];
+
+    for &(name, count, saved_count) in &rows {
+        match saved_count {
+            Some(saved_count) => writeln!(out, "{}\t{}\t{}", name, count, saved_count)?,
+            None => writeln!(out, "{}\t{}", name, count)?
+        }
+    }
+    Ok(())
+}
+
+
+// Reader counting the events, usable with any of the mso_doc parse
+// functions.
+#[derive(Default)]
+pub struct StatsCounter {
+    stats: Stats,
+    in_table: bool
+}
+
+impl StatsCounter {
+    pub fn finish(self) -> Stats {
+        self.stats
+    }
+
+    fn count_text(&mut self, text: &str) {
+        self.stats.words += text.unicode_words().count() as u64;
+        for c in text.chars() {
+            self.stats.characters += 1;
+            if !c.is_whitespace() {
+                self.stats.characters_without_spaces += 1;
+            }
+        }
+    }
+}
+
+impl mso_doc::WordReader for StatsCounter {
+    fn paragraph_row(&mut self, text: &str, _style: &str) {
+        self.in_table = false;
+        if !text.is_empty() {
+            self.stats.paragraphs += 1;
+        }
+        self.count_text(text);
+    }
+
+    fn heading_row(&mut self, text: &str, style: &str, _level: u32) {
+        self.stats.headings += 1;
+        self.paragraph_row(text, style);
+    }
+
+    fn table_new_row(&mut self) {
+        if !self.in_table {
+            self.stats.tables += 1;
+            self.in_table = true;
+        }
+    }
+
+    fn table_closed(&mut self) {
+        self.in_table = false;
+    }
+
+    // a cell of several paragraphs sends one of these each
+    fn table_cell(&mut self, text: &str, _style: &str, _header: bool) {
+        self.table_new_row();
+        self.count_text(text);
+    }
+
+    fn table_cell_closed(&mut self) {
+        self.stats.cells += 1;
+    }
+
+    fn section_break(&mut self) {
+        self.in_table = false;
+    }
+
+    fn note(&mut self, kind: NoteKind, _id: &str, _text: &str) {
+        match kind {
+            NoteKind::Footnote => self.stats.footnotes += 1,
+            NoteKind::Endnote => self.stats.endnotes += 1
+        }
+    }
+
+    fn image(&mut self) {
+        self.stats.images += 1;
+    }
+}
@@ -0,0 +1,61 @@
+extern crate mso_to_text;
+
+mod common;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use common::*;
+
+
+// Empty directory of the test, removed first if left by a previous run.
+fn test_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("mso_to_text-batch-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn run(dir: &PathBuf, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_mso_to_text"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn same_output() {
+    let dir = test_dir("same-output");
+    for sub in &["a", "b"] {
+        fs::create_dir_all(dir.join(sub)).unwrap();
+        fs::write(dir.join(sub).join("x.docx"), docx(&paragraph("", sub))).unwrap();
+    }
+
+    let output = run(&dir, &["a/x.docx", "b/x.docx", "--output-dir", "out"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("a/x.docx and b/x.docx would both be converted"));
+    assert!(!dir.join("out").exists());
+
+    // the same name in the mirrored tree is no conflict
+    let output = run(&dir, &[".", "--output-dir", "out"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(fs::read_to_string(dir.join("out/a/x.txt")).unwrap(), "\ta\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn directory_links() {
+    let dir = test_dir("links");
+    fs::create_dir_all(dir.join("docs")).unwrap();
+    fs::write(dir.join("docs/x.docx"), docx(&paragraph("", "x"))).unwrap();
+    std::os::unix::fs::symlink("..", dir.join("docs/up")).unwrap();
+
+    let output = run(&dir, &["docs"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "\tx\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,31 @@
+extern crate mso_to_text;
+
+mod common;
+
+use common::*;
+
+
+fn contract() -> Vec<u8> {
+    let body = paragraph("", "Preamble before any heading")
+        + &paragraph("Heading1", "Contract")
+        + &paragraph("", "Between the parties.")
+        + &paragraph("Heading2", "3. Payment")
+        + &paragraph("", "")
+        + &paragraph("Heading3", "3.1 Terms")
+        + &paragraph("", "Payment is due in thirty days.")
+        + &paragraph("", "Late payments bear interests.")
+        + &table(true, &[&["Delay", "Rate"], &["30 days", "1%"]])
+        + &paragraph("Heading2", "4. Term")
+        + &paragraph("", "One year.");
+    docx_with(&body, &[("styles", "styles.xml", &heading_styles())])
+}
+
+#[test]
+fn heading_paths() {
+    assert_golden("chunks.jsonl", &to_chunks(&contract(), 2000));
+}
+
+#[test]
+fn split_between_paragraphs() {
+    assert_golden("chunks_split.jsonl", &to_chunks(&contract(), 40));
+}
@@ -0,0 +1,339 @@
+// Fixtures built from XML strings, and comparison of the conversions with
+// the golden files of tests/golden. Running the tests with UPDATE_GOLDEN
+// set rewrites the golden files instead.
+#![allow(dead_code)]
+
+extern crate zip;
+extern crate serde_json;
+
+use std::env;
+use std::fs;
+use std::io;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use mso_to_text::mso_doc;
+use mso_to_text::converters::{TextConverter, CsvConverter};
+use mso_to_text::document::DocumentBuilder;
+use mso_to_text::outline;
+use mso_to_text::chunks;
+
+
+pub const W_NAMESPACE: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+const RELATIONSHIPS: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+
+// Zip package of the given parts.
+pub fn package(parts: &[(&str, &str)]) -> Vec<u8> {
+    let mut writer = zip::ZipWriter::new(io::Cursor::new(Vec::new()));
+    for &(name, content) in parts {
+        writer.start_file(name, zip::write::FileOptions::default()).unwrap();
+        writer.write_all(content.as_bytes()).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+// Word document of the given body, with its parts related to the main part
+// as (relationship type, part name, content).
+pub fn docx_with(body: &str, related: &[(&str, &str, &str)]) -> Vec<u8> {
+    docx_package(body, related, &[])
+}
+
+// Word document with parts related to the package too, their names being
+// from the root of the package.
+pub fn docx_package(body: &str, related: &[(&str, &str, &str)], package_related: &[(&str, &str, &str)]) -> Vec<u8> {
+    docx_main_part("word/document.xml", body, related, package_related)
+}
+
+// Word document of the given main part name, its related parts being next
+// to it.
+pub fn docx_main_part(main_part: &str, body: &str, related: &[(&str, &str, &str)],
+                      package_related: &[(&str, &str, &str)]) -> Vec<u8> {
+    let (dir, file) = match main_part.rfind('/') {
+        Some(i) => main_part.split_at(i + 1),
+        None => ("", main_part)
+    };
+    let content_types = format!("<?xml version=\"1.0\"?>\
+        <Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+        <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+        <Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+        <Override PartName=\"/{}\" \
+        ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml\"/>\
+        </Types>", main_part);
+    let package_rels = format!("<?xml version=\"1.0\"?>\
+        <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+        <Relationship Id=\"rId1\" Type=\"{}/officeDocument\" Target=\"{}\"/>{}\
+        </Relationships>", RELATIONSHIPS, main_part,
+        package_related.iter().enumerate()
+            .map(|(i, &(rel_type, name, _))| format!(
+                "<Relationship Id=\"rId{}\" Type=\"{}/{}\" Target=\"{}\"/>", i + 2, RELATIONSHIPS, rel_type, name))
+            .collect::<String>());
+    let document = format!("<?xml version=\"1.0\"?><w:document xmlns:w=\"{}\"><w:body>{}</w:body></w:document>",
+                           W_NAMESPACE, body);
+    let document_rels = format!("<?xml version=\"1.0\"?>\
+        <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">{}</Relationships>",
+        related.iter().enumerate()
+            .map(|(i, &(rel_type, name, _))| format!(
+                "<Relationship Id=\"rId{}\" Type=\"{}/{}\" Target=\"{}\"/>", i + 1, RELATIONSHIPS, rel_type, name))
+            .collect::<String>());
+    let related_parts: Vec<(String, &str)> = related.iter()
+        .map(|&(_, name, content)| (format!("{}{}", dir, name), content))
+        .collect();
+    let document_rels_part = format!("{}_rels/{}.rels", dir, file);
+
+    let mut parts = vec![
+        ("[Content_Types].xml", content_types.as_str()),
+        ("_rels/.rels", package_rels.as_str()),
+        (main_part, document.as_str()),
+        (document_rels_part.as_str(), document_rels.as_str())
+    ];
+    parts.extend(related_parts.iter().map(|&(ref name, content)| (name.as_str(), content)));
+    parts.extend(package_related.iter().map(|&(_, name, content)| (name, content)));
+    package(&parts)
+}
+
+pub fn docx(body: &str) -> Vec<u8> {
+    docx_with(body, &[])
+}
+
+// Flat OPC document of the parts of a zipped package, as Word saves a
+// "Word XML Document".
+pub fn flat_opc(document: &[u8]) -> Vec<u8> {
+    let mut archive = zip::ZipArchive::new(io::Cursor::new(document)).unwrap();
+    let mut flat = "<?xml version=\"1.0\" standalone=\"yes\"?>\n\
+        <pkg:package xmlns:pkg=\"http://schemas.microsoft.com/office/2006/xmlPackage\">\n".to_owned();
+    for i in 0..archive.len() {
+        let mut part = archive.by_index(i).unwrap();
+        let content_type = match part.name() {
+            "[Content_Types].xml" => continue,
+            "word/document.xml" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml",
+            name if name.ends_with(".rels") => "application/vnd.openxmlformats-package.relationships+xml",
+            _ => "application/xml"
+        };
+        let name = format!("/{}", part.name());
+        let mut content = String::new();
+        part.read_to_string(&mut content).unwrap();
+        let content = match content.find("?>") {
+            Some(end) if content.starts_with("<?xml") => content[end + 2..].to_owned(),
+            _ => content
+        };
+        flat.push_str(&format!("<pkg:part pkg:name=\"{}\" pkg:contentType=\"{}\">\n\
+            <pkg:xmlData>{}</pkg:xmlData></pkg:part>\n", name, content_type, content));
+    }
+    flat.push_str("</pkg:package>\n");
+    flat.into_bytes()
+}
+
+// OpenDocument of the given mime type and body of content.xml, as
+// <office:text> or <office:spreadsheet>.
+pub fn opendocument(mimetype: &str, body: &str) -> Vec<u8> {
+    let content = format!("<?xml version=\"1.0\"?>\
+        <office:document-content xmlns:office=\"urn:oasis:names:tc:opendocument:xmlns:office:1.0\" \
+        xmlns:style=\"urn:oasis:names:tc:opendocument:xmlns:style:1.0\" \
+        xmlns:text=\"urn:oasis:names:tc:opendocument:xmlns:text:1.0\" \
+        xmlns:table=\"urn:oasis:names:tc:opendocument:xmlns:table:1.0\">\
+        <office:body>{}</office:body></office:document-content>", body);
+    package(&[("mimetype", mimetype), ("content.xml", &content)])
+}
+
+// Compound file of the given streams under the root storage, in sectors
+// of 512 bytes. Streams are padded to the mini stream cutoff, for them all
+// to be regular streams.
+pub fn compound_file(streams: &[(&str, &[u8])]) -> Vec<u8> {
+    const SECTOR: usize = 512;
+    const CUTOFF: usize = 4096;
+    const END_OF_CHAIN: u32 = 0xFFFF_FFFE;
+    const FREE: u32 = 0xFFFF_FFFF;
+
+    let streams: Vec<(&str, Vec<u8>)> = streams.iter()
+        .map(|&(name, content)| {
+            let mut content = content.to_vec();
+            let size = content.len().max(CUTOFF).div_ceil(SECTOR) * SECTOR;
+            content.resize(size, 0);
+            (name, content)
+        })
+        .collect();
+
+    // FAT in sector 0, directory, then the streams
+    let dir_sectors = (streams.len() + 1).div_ceil(SECTOR / 128);
+    let mut fat = vec![0xFFFF_FFFDu32];
+    let chain = |fat: &mut Vec<u32>, count: usize| -> u32 {
+        let start = fat.len() as u32;
+        for i in 0..count {
+            fat.push(if i + 1 == count { END_OF_CHAIN } else { start + i as u32 + 1 });
+        }
+        start
+    };
+    let dir_start = chain(&mut fat, dir_sectors);
+    let starts: Vec<u32> = streams.iter().map(|(_, content)| chain(&mut fat, content.len() / SECTOR)).collect();
+    assert!(fat.len() <= SECTOR / 4, "streams fit a single FAT sector");
+    fat.resize(SECTOR / 4, FREE);
+
+    let mut header = vec![0u8; SECTOR];
+    header[..8].copy_from_slice(b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1");
+    let fields: [(usize, u32, usize); 10] = [
+        (0x18, 0x3E, 2), (0x1A, 3, 2), (0x1C, 0xFFFE, 2), (0x1E, 9, 2), (0x20, 6, 2),
+        (0x2C, 1, 4), (0x30, dir_start, 4), (0x38, CUTOFF as u32, 4), (0x3C, END_OF_CHAIN, 4),
+        (0x44, END_OF_CHAIN, 4)
+    ];
+    for &(offset, value, size) in &fields {
+        header[offset..offset + size].copy_from_slice(&value.to_le_bytes()[..size]);
+    }
+    for i in 0..109 {
+        let sector = if i == 0 { 0 } else { FREE };
+        header[0x4C + i * 4..0x50 + i * 4].copy_from_slice(&sector.to_le_bytes());
+    }
+
+    // the root, its child the first stream, each stream the right sibling
+    // of the previous one
+    let mut directory = vec![0u8; dir_sectors * SECTOR];
+    let entries = (0..dir_sectors * SECTOR / 128).map(|i| {
+        match i {
+            0 => Some(("Root Entry", 5, FREE, if streams.is_empty() { FREE } else { 1 }, END_OF_CHAIN, 0)),
+            i if i <= streams.len() => {
+                let right = if i < streams.len() { i as u32 + 1 } else { FREE };
+                Some((streams[i - 1].0, 2, right, FREE, starts[i - 1], streams[i - 1].1.len()))
+            },
+            _ => None
+        }
+    });
+    for (i, entry) in entries.enumerate() {
+        let raw = &mut directory[i * 128..(i + 1) * 128];
+        for offset in &[68, 72, 76] {
+            raw[*offset..*offset + 4].copy_from_slice(&FREE.to_le_bytes());
+        }
+        if let Some((name, entry_type, right, child, start, size)) = entry {
+            let name: Vec<u16> = name.encode_utf16().chain(Some(0)).collect();
+            for (j, unit) in name.iter().enumerate() {
+                raw[j * 2..j * 2 + 2].copy_from_slice(&unit.to_le_bytes());
+            }
+            raw[64..66].copy_from_slice(&(name.len() as u16 * 2).to_le_bytes());
+            raw[66] = entry_type;
+            raw[67] = 1;
+            raw[72..76].copy_from_slice(&right.to_le_bytes());
+            raw[76..80].copy_from_slice(&child.to_le_bytes());
+            raw[116..120].copy_from_slice(&start.to_le_bytes());
+            raw[120..124].copy_from_slice(&(size as u32).to_le_bytes());
+        }
+    }
+
+    let mut file = header;
+    file.extend(fat.iter().flat_map(|sector| sector.to_le_bytes().to_vec()));
+    file.extend(directory);
+    for (_, content) in streams {
+        file.extend(content);
+    }
+    file
+}
+
+// Paragraph of a single run, with the given style if any.
+pub fn paragraph(style: &str, text: &str) -> String {
+    let properties = if style.is_empty() {
+        String::new()
+    } else {
+        format!("<w:pPr><w:pStyle w:val=\"{}\"/></w:pPr>", style)
+    };
+    format!("<w:p>{}<w:r><w:t xml:space=\"preserve\">{}</w:t></w:r></w:p>", properties, text)
+}
+
+// Table of rows of single paragraph cells, the first row being a header
+// row if asked.
+pub fn table(header: bool, rows: &[&[&str]]) -> String {
+    let rows: String = rows.iter().enumerate()
+        .map(|(i, cells)| {
+            let properties = if header && i == 0 {
+                "<w:trPr><w:tblHeader w:val=\"true\"/></w:trPr>"
+            } else {
+                ""
+            };
+            let cells: String = cells.iter()
+                .map(|text| format!("<w:tc>{}</w:tc>", paragraph("", text)))
+                .collect();
+            format!("<w:tr>{}{}</w:tr>", properties, cells)
+        })
+        .collect();
+    format!("<w:tbl>{}</w:tbl>", rows)
+}
+
+// Heading1 and Heading2 with their outline level, MyHeading based on
+// Heading2, and Heading3 only known by its name.
+pub fn heading_styles() -> String {
+    format!("<?xml version=\"1.0\"?><w:styles xmlns:w=\"{}\">\
+        <w:style w:type=\"paragraph\" w:styleId=\"Normal\"><w:name w:val=\"Normal\"/></w:style>\
+        <w:style w:type=\"paragraph\" w:styleId=\"Heading1\"><w:name w:val=\"heading 1\"/>\
+        <w:basedOn w:val=\"Normal\"/><w:pPr><w:outlineLvl w:val=\"0\"/></w:pPr></w:style>\
+        <w:style w:type=\"paragraph\" w:styleId=\"Heading2\"><w:name w:val=\"heading 2\"/>\
+        <w:basedOn w:val=\"Normal\"/><w:pPr><w:outlineLvl w:val=\"1\"/></w:pPr></w:style>\
+        <w:style w:type=\"paragraph\" w:styleId=\"MyHeading\"><w:name w:val=\"My heading\"/>\
+        <w:basedOn w:val=\"Heading2\"/></w:style>\
+        <w:style w:type=\"paragraph\" w:styleId=\"Heading3\"><w:name w:val=\"Heading 3\"/></w:style>\
+        <w:style w:type=\"character\" w:styleId=\"Strong\"><w:name w:val=\"heading 1\"/></w:style>\
+        </w:styles>", W_NAMESPACE)
+}
+
+
+pub fn to_text(document: &[u8]) -> String {
+    let mut converter = TextConverter::new(Vec::new());
+    parse(document, &mut converter);
+    String::from_utf8(converter.finish().unwrap()).unwrap()
+}
+
+pub fn to_csv(document: &[u8]) -> String {
+    let mut converter = CsvConverter::new(Vec::new());
+    parse(document, &mut converter);
+    String::from_utf8(converter.finish().unwrap()).unwrap()
+}
+
+pub fn to_json(document: &[u8]) -> String {
+    let mut builder = DocumentBuilder::default();
+    parse(document, &mut builder);
+    serde_json::to_string_pretty(&builder.finish()).unwrap() + "\n"
+}
+
+pub fn to_outline(document: &[u8]) -> String {
+    let mut builder = DocumentBuilder::default();
+    parse(document, &mut builder);
+    let mut out = Vec::new();
+    outline::write_text(&outline::headings(&builder.finish()), &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+pub fn to_outline_json(document: &[u8]) -> String {
+    let mut builder = DocumentBuilder::default();
+    parse(document, &mut builder);
+    serde_json::to_string_pretty(&outline::headings(&builder.finish())).unwrap() + "\n"
+}
+
+pub fn to_chunks(document: &[u8], max_size: usize) -> String {
+    let mut builder = DocumentBuilder::default();
+    parse(document, &mut builder);
+    let mut out = Vec::new();
+    chunks::write_json_lines(&chunks::chunks(&builder.finish(), max_size), "test.docx", &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+pub fn parse(document: &[u8], reader: &mut dyn mso_doc::WordReader) {
+    mso_doc::parse_reader(io::Cursor::new(document), &mso_doc::Options::default(), reader)
+        .expect("document converted");
+}
+
+
+// Compares the conversion with the golden file tests/golden/<name>.
+pub fn assert_golden(name: &str, actual: &str) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "golden", name].iter().collect();
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("{}: {}, run with UPDATE_GOLDEN=1 to create it", path.display(), err));
+    assert!(expected == actual, "{} differs:\n--- expected\n{}\n--- actual\n{}", name, expected, actual);
+}
+
+// Golden comparisons of the conversions in every format.
+pub fn assert_all_golden(name: &str, document: &[u8]) {
+    assert_golden(&format!("{}.txt", name), &to_text(document));
+    assert_golden(&format!("{}.csv", name), &to_csv(document));
+    assert_golden(&format!("{}.json", name), &to_json(document));
+}
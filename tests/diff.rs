@@ -0,0 +1,79 @@
+extern crate mso_to_text;
+
+mod common;
+
+use mso_to_text::diff::{self, Change, Paragraphs};
+use common::*;
+
+
+fn changes(old: &str, new: &str) -> Vec<Change> {
+    let paragraphs = |body: &str| {
+        let mut paragraphs = Paragraphs::default();
+        parse(&docx(body), &mut paragraphs);
+        paragraphs.finish()
+    };
+    diff::diff(&paragraphs(old), &paragraphs(new))
+}
+
+fn versions() -> (String, String) {
+    let old = paragraph("Title", "Sale contract")
+        + &paragraph("", "The buyer pays within 30 days of the invoice.")
+        + &table(true, &[&["Delay", "Rate"], &["Over 30 days", "1.5%"]])
+        + &paragraph("", "Signed in Paris")
+        + &paragraph("", "Any dispute goes to the courts of Paris.");
+    let new = paragraph("Title", "Sale contract")
+        + &paragraph("", "The buyer pays within 60 days of the invoice date.")
+        + &table(true, &[&["Delay", "Rate"], &["Over 60 days", "1.5%"]])
+        + &paragraph("", "Signed in Lyon")
+        + &paragraph("", "The seller keeps the ownership until paid.");
+    (old, new)
+}
+
+#[test]
+fn unified() {
+    let (old, new) = versions();
+    let mut out = Vec::new();
+    diff::write_unified(&changes(&old, &new), "old.docx", "new.docx", 1, &mut out).unwrap();
+    assert_golden("diff_unified.txt", &String::from_utf8(out).unwrap());
+}
+
+#[test]
+fn side_by_side() {
+    let (old, new) = versions();
+    let mut out = Vec::new();
+    diff::write_side_by_side(&changes(&old, &new), 60, &mut out).unwrap();
+    assert_golden("diff_side_by_side.txt", &String::from_utf8(out).unwrap());
+}
+
+#[test]
+fn formatting_ignored() {
+    // same text in other runs, spacing and style
+    let old = paragraph("", "Payment terms");
+    let new = "<w:p><w:pPr><w:pStyle w:val=\"Heading1\"/></w:pPr>\
+        <w:r><w:rPr><w:b/></w:rPr><w:t xml:space=\"preserve\">Payment  </w:t></w:r>\
+        <w:r><w:t>terms</w:t></w:r></w:p>";
+    assert_eq!(changes(&old, new), vec![Change::Equal("Payment terms".to_owned())]);
+}
+
+#[test]
+fn words() {
+    assert_eq!(
+        diff::word_diff("pays within 30 days", "pays within 60 business days"),
+        ("pays within [-30-] days".to_owned(), "pays within {+60 business+} days".to_owned()));
+}
+
+#[test]
+fn long_documents() {
+    // too long for a table of every pair of paragraphs
+    let old: Vec<String> = (0..200_000).map(|i| format!("Clause {}", i)).collect();
+    let mut new = old.clone();
+    new[150_000].push_str(" amended");
+    new.remove(1000);
+    let changed: Vec<Change> = diff::diff(&old, &new).into_iter()
+        .filter(|change| !matches!(*change, Change::Equal(_)))
+        .collect();
+    assert_eq!(changed, vec![
+        Change::Deleted("Clause 1000".to_owned()),
+        Change::Changed("Clause 150000".to_owned(), "Clause 150000 {+amended+}".to_owned())
+    ]);
+}
@@ -0,0 +1,198 @@
+extern crate mso_to_text;
+
+mod common;
+
+use std::io;
+
+use mso_to_text::mso_doc;
+use mso_to_text::mso_doc::MsoError;
+use mso_to_text::converters::TextConverter;
+use common::*;
+
+
+const TEST_DOC: &[u8] = include_bytes!("../fuzz/seeds/parse_reader/test.doc");
+
+// where the text and the FKP page are written in the WordDocument stream
+const TEXT_FC: usize = 0x400;
+const FKP_PAGE: usize = 4;
+
+// Paragraph of a Word binary document: its text ended by its mark, style
+// index, and sprms with a single byte operand.
+struct Paragraph<'a> {
+    text: &'a str,
+    istd: u16,
+    sprms: &'a [(u16, u8)]
+}
+
+const IN_TABLE: (u16, u8) = (0x2416, 1);
+const ROW_END: (u16, u8) = (0x2417, 1);
+const HEADER_ROW: (u16, u8) = (0x3404, 1);
+
+fn push_u16(data: &mut Vec<u8>, value: u16) {
+    data.extend_from_slice(&value.to_le_bytes());
+}
+
+fn push_u32(data: &mut Vec<u8>, value: u32) {
+    data.extend_from_slice(&value.to_le_bytes());
+}
+
+// Word 97 document of single byte paragraphs, in a single piece unless
+// the character positions of the piece table are given, with the styles
+// Normal, heading 1 and Table Text, and sections ending at the given
+// character positions.
+fn word_binary(paragraphs: &[Paragraph], sections: &[u32], pieces: Option<&[u32]>) -> Vec<u8> {
+    let text: String = paragraphs.iter().map(|paragraph| paragraph.text).collect();
+    let ccp = text.len() as u32;
+    let mut table = Vec::new();
+
+    // CLX, the piece table
+    let default_cps = [0, ccp];
+    let cps = pieces.unwrap_or(&default_cps);
+    let clx = (0, 5 + cps.len() * 4 + (cps.len() - 1) * 8);
+    table.push(0x02);
+    push_u32(&mut table, (cps.len() * 4 + (cps.len() - 1) * 8) as u32);
+    for &cp in cps {
+        push_u32(&mut table, cp);
+    }
+    for &cp in &cps[..cps.len() - 1] {
+        push_u16(&mut table, 0);
+        push_u32(&mut table, ((TEXT_FC as u32 + cp) * 2) | 0x4000_0000);
+        push_u16(&mut table, 0);
+    }
+
+    // STSH, with a base of 10 bytes per style
+    let stsh_start = table.len();
+    push_u16(&mut table, 4);
+    push_u16(&mut table, 3);
+    push_u16(&mut table, 10);
+    for &(sti, name) in &[(0u16, "Normal"), (1, "heading 1"), (0x0FFE, "Table Text")] {
+        let mut std = vec![0u8; 10];
+        std[..2].copy_from_slice(&sti.to_le_bytes());
+        push_u16(&mut std, name.len() as u16);
+        for unit in name.encode_utf16() {
+            push_u16(&mut std, unit);
+        }
+        push_u16(&mut table, std.len() as u16);
+        table.extend(std);
+    }
+    let stsh = (stsh_start, table.len() - stsh_start);
+
+    // PlcfSed
+    let sed_start = table.len();
+    let mut section_cps = vec![0];
+    section_cps.extend_from_slice(sections);
+    section_cps.push(ccp);
+    for &cp in &section_cps {
+        push_u32(&mut table, cp);
+    }
+    table.extend(vec![0u8; (section_cps.len() - 1) * 12]);
+    let plcf_sed = (sed_start, table.len() - sed_start);
+
+    // PlcBtePapx, a single FKP page for all the paragraphs
+    let bte_start = table.len();
+    push_u32(&mut table, TEXT_FC as u32);
+    push_u32(&mut table, TEXT_FC as u32 + ccp);
+    push_u32(&mut table, FKP_PAGE as u32);
+    let plcf_bte_papx = (bte_start, table.len() - bte_start);
+
+    // FKP: run bounds, then the offsets of the PapxInFkp stored from the
+    // end of the page
+    let mut fkp = vec![0u8; 512];
+    let crun = paragraphs.len();
+    let mut fc = TEXT_FC as u32;
+    let mut papx_end = 511;
+    for (i, paragraph) in paragraphs.iter().enumerate() {
+        fkp[i * 4..i * 4 + 4].copy_from_slice(&fc.to_le_bytes());
+        fc += paragraph.text.len() as u32;
+
+        let mut papx = paragraph.istd.to_le_bytes().to_vec();
+        for &(sprm, operand) in paragraph.sprms {
+            push_u16(&mut papx, sprm);
+            papx.push(operand);
+        }
+        if papx.len() % 2 == 1 {
+            papx.push(0);
+        }
+        let start = (papx_end - papx.len() - 2) & !1;
+        fkp[start] = 0;
+        fkp[start + 1] = (papx.len() / 2) as u8;
+        fkp[start + 2..start + 2 + papx.len()].copy_from_slice(&papx);
+        fkp[(crun + 1) * 4 + i * 13] = (start / 2) as u8;
+        papx_end = start;
+    }
+    fkp[crun * 4..crun * 4 + 4].copy_from_slice(&fc.to_le_bytes());
+    fkp[511] = crun as u8;
+
+    // FIB, text, then the FKP page
+    let mut word_document = vec![0u8; FKP_PAGE * 512];
+    word_document[0..2].copy_from_slice(&0xA5ECu16.to_le_bytes());
+    word_document[2..4].copy_from_slice(&0xC1u16.to_le_bytes());
+    word_document[0x0A..0x0C].copy_from_slice(&0x0200u16.to_le_bytes());
+    word_document[0x4C..0x50].copy_from_slice(&ccp.to_le_bytes());
+    for &(index, (fc, lcb)) in &[(1, stsh), (6, plcf_sed), (13, plcf_bte_papx), (33, clx)] {
+        let offset = 0x9A + index * 8;
+        word_document[offset..offset + 4].copy_from_slice(&(fc as u32).to_le_bytes());
+        word_document[offset + 4..offset + 8].copy_from_slice(&(lcb as u32).to_le_bytes());
+    }
+    word_document[TEXT_FC..TEXT_FC + text.len()].copy_from_slice(text.as_bytes());
+    word_document.extend(fkp);
+
+    compound_file(&[("WordDocument", &word_document), ("1Table", &table)])
+}
+
+fn paragraph<'a>(text: &'a str, istd: u16, sprms: &'a [(u16, u8)]) -> Paragraph<'a> {
+    Paragraph { text, istd, sprms }
+}
+
+
+#[test]
+fn document() {
+    // heading style, a table, and a field of which only the result is kept
+    assert_golden("doc.txt", &to_text(TEST_DOC));
+    assert_golden("doc.csv", &to_csv(TEST_DOC));
+}
+
+#[test]
+fn header_rows_and_sections() {
+    let paragraphs = [
+        paragraph("Title\r", 1, &[]),
+        paragraph("Name\x07", 2, &[IN_TABLE]),
+        paragraph("Value\x07", 2, &[IN_TABLE]),
+        paragraph("\x07", 0, &[IN_TABLE, ROW_END, HEADER_ROW]),
+        paragraph("x\x07", 0, &[IN_TABLE]),
+        paragraph("1\x07", 0, &[IN_TABLE]),
+        paragraph("\x07", 0, &[IN_TABLE, ROW_END]),
+        paragraph("First section\x0C", 0, &[]),
+        paragraph("Page\x0Cbreak\r", 0, &[]),
+        paragraph("Last\r", 0, &[])
+    ];
+    // the second section starts after the first section mark
+    let section_start: usize = paragraphs[..8].iter().map(|paragraph| paragraph.text.len()).sum();
+    let document = word_binary(&paragraphs, &[section_start as u32], None);
+    assert_all_golden("doc_header_rows_and_sections", &document);
+
+    // the same document as docx
+    let body = "<w:p><w:pPr><w:pStyle w:val=\"heading 1\"/><w:outlineLvl w:val=\"0\"/></w:pPr>\
+        <w:r><w:t>Title</w:t></w:r></w:p>\
+        <w:tbl><w:tr><w:trPr><w:tblHeader w:val=\"true\"/></w:trPr>\
+        <w:tc><w:p><w:pPr><w:pStyle w:val=\"Table Text\"/></w:pPr><w:r><w:t>Name</w:t></w:r></w:p></w:tc>\
+        <w:tc><w:p><w:pPr><w:pStyle w:val=\"Table Text\"/></w:pPr><w:r><w:t>Value</w:t></w:r></w:p></w:tc></w:tr>\
+        <w:tr><w:tc><w:p><w:pPr><w:pStyle w:val=\"Normal\"/></w:pPr><w:r><w:t>x</w:t></w:r></w:p></w:tc>\
+        <w:tc><w:p><w:pPr><w:pStyle w:val=\"Normal\"/></w:pPr><w:r><w:t>1</w:t></w:r></w:p></w:tc></w:tr></w:tbl>\
+        <w:p><w:pPr><w:pStyle w:val=\"Normal\"/><w:sectPr/></w:pPr><w:r><w:t>First section</w:t></w:r></w:p>\
+        <w:p><w:pPr><w:pStyle w:val=\"Normal\"/></w:pPr><w:r><w:t>Page</w:t><w:br w:type=\"page\"/>\
+        <w:t>break</w:t></w:r></w:p>\
+        <w:p><w:pPr><w:pStyle w:val=\"Normal\"/></w:pPr><w:r><w:t>Last</w:t></w:r></w:p>";
+    assert_eq!(to_json(&document), to_json(&docx(body)));
+}
+
+#[test]
+fn pieces_out_of_order() {
+    let paragraphs = [paragraph("Some text\r", 0, &[])];
+    let document = word_binary(&paragraphs, &[], Some(&[5, 0, 10]));
+    let mut converter = TextConverter::new(io::sink());
+    match mso_doc::parse_reader(io::Cursor::new(document), &mso_doc::Options::default(), &mut converter) {
+        Err(MsoError::BadWordDocument(ref message)) if message.contains("out of order") => (),
+        other => panic!("expected pieces out of order, got {:?}", other)
+    }
+}
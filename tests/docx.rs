@@ -0,0 +1,129 @@
+extern crate mso_to_text;
+
+mod common;
+
+use std::io;
+
+use mso_to_text::mso_doc;
+use mso_to_text::document::DocumentBuilder;
+use mso_to_text::stats::StatsCounter;
+use common::*;
+
+
+#[test]
+fn paragraphs() {
+    let body = paragraph("", "First paragraph")
+        + &paragraph("", "  spaces kept  ")
+        + "<w:p><w:r><w:t>Split </w:t></w:r><w:r><w:t>in runs</w:t></w:r></w:p>"
+        + "<w:p/>";
+    assert_all_golden("paragraphs", &docx(&body));
+}
+
+#[test]
+fn styles() {
+    let body = paragraph("Title", "The title")
+        + &paragraph("Heading1", "A heading")
+        + &paragraph("", "Normal text");
+    assert_all_golden("styles", &docx(&body));
+}
+
+#[test]
+fn tables() {
+    let body = paragraph("", "Before")
+        + &table(false, &[&["a", "b"], &["c, with comma", "d \"quoted\""]])
+        + &paragraph("", "Between")
+        + &table(false, &[&["e"]])
+        + &paragraph("", "After");
+    assert_all_golden("tables", &docx(&body));
+}
+
+#[test]
+fn header_rows() {
+    let body = table(true, &[&["Name", "Value"], &["x", "1"], &["y", "2"]]);
+    assert_all_golden("header_rows", &docx(&body));
+}
+
+#[test]
+fn cell_paragraphs() {
+    let body = "<w:tbl><w:tr><w:tc>".to_owned()
+        + &paragraph("", "first line") + &paragraph("", "second line")
+        + "</w:tc><w:tc>" + &paragraph("", "other") + "</w:tc></w:tr></w:tbl>";
+    assert_all_golden("cell_paragraphs", &docx(&body));
+}
+
+#[test]
+fn sections_and_notes() {
+    let body = "<w:p><w:pPr><w:sectPr/></w:pPr><w:r><w:t>First section</w:t></w:r></w:p>".to_owned()
+        + &paragraph("", "Second section");
+    let footnotes = format!(
+        "<?xml version=\"1.0\"?><w:footnotes xmlns:w=\"{}\">\
+        <w:footnote w:type=\"separator\" w:id=\"-1\"><w:p><w:r><w:separator/></w:r></w:p></w:footnote>\
+        <w:footnote w:id=\"1\">{}</w:footnote></w:footnotes>",
+        W_NAMESPACE, paragraph("", "A footnote"));
+    let document = docx_with(&body, &[("footnotes", "footnotes.xml", &footnotes)]);
+    assert_all_golden("sections_and_notes", &document);
+    // the same parts inlined in a single XML file
+    assert_all_golden("sections_and_notes", &flat_opc(&document));
+}
+
+#[test]
+fn flat_opc_styles() {
+    let body = paragraph("Heading1", "Contract") + &paragraph("", "Body");
+    let document = docx_with(&body, &[("styles", "styles.xml", &heading_styles())]);
+    assert_eq!(to_outline(&flat_opc(&document)), to_outline(&document));
+    assert_eq!(to_outline(&document), "1. Contract\n");
+}
+
+#[test]
+fn main_part_from_relationships() {
+    // a main part other than word/document.xml, and a stray one at this name
+    let body = paragraph("Heading1", "Contract") + &paragraph("", "Body");
+    let stray = format!("<?xml version=\"1.0\"?><w:document xmlns:w=\"{}\"><w:body>{}</w:body></w:document>",
+                        W_NAMESPACE, paragraph("", "Stray"));
+    let document = docx_main_part("word/document2.xml", &body, &[("styles", "styles.xml", &heading_styles())],
+                                  &[("", "word/document.xml", &stray)]);
+    assert_eq!(to_text(&document), "Heading1\tContract\n\tBody\n");
+    assert_eq!(to_outline(&document), "1. Contract\n");
+}
+
+#[test]
+fn flat_opc_markup() {
+    // part tags quoted in comments, CDATA sections and attribute values are
+    // not parts, whatever the bytes before them
+    let document = docx(&(paragraph("", "Caf\u{e9} \u{2014} \u{1F600}") + &paragraph("", "&lt;pkg:part&gt; as text")));
+    let flat = String::from_utf8(flat_opc(&document)).unwrap()
+        .replacen("<pkg:part ", "<!-- <pkg:part pkg:name=\"/word/document.xml\"> -->\
+            <![CDATA[<pkg:part pkg:name=\"/word/document.xml\">]]><pkg:part x=\"/>\" ", 1);
+    assert_eq!(to_text(flat.as_bytes()), to_text(&document));
+}
+
+#[test]
+fn word2003_notes_and_pictures() {
+    let document = format!("<?xml version=\"1.0\"?><w:wordDocument xmlns:w=\"{}\">\
+        <w:docPr><w:footnotePr><w:footnote w:type=\"separator\"><w:p><w:r><w:separator/></w:r></w:p></w:footnote>\
+        </w:footnotePr></w:docPr><w:body>\
+        <w:p><w:r><w:t>Text</w:t></w:r><w:r><w:footnote>{}</w:footnote></w:r>\
+        <w:r><w:endnote>{}</w:endnote></w:r><w:r><w:footnote>{}</w:footnote></w:r></w:p>\
+        <w:p><w:r><w:pict><w:binData w:name=\"wordml://1.png\">iVBORw0KGgo=</w:binData></w:pict></w:r></w:p>\
+        <w:sectPr><w:hdr><w:p><w:r><w:t>header</w:t></w:r></w:p></w:hdr></w:sectPr>\
+        </w:body></w:wordDocument>",
+        "http://schemas.microsoft.com/office/word/2003/wordml",
+        "<w:p><w:r><w:t>First footnote</w:t></w:r></w:p>",
+        "<w:p><w:r><w:t>An endnote</w:t></w:r></w:p>",
+        "<w:p><w:r><w:t>Second footnote</w:t></w:r></w:p>");
+    assert_golden("word2003.json", &to_json(document.as_bytes()));
+    let mut counter = StatsCounter::default();
+    parse(document.as_bytes(), &mut counter);
+    assert_eq!(counter.finish().images, 1);
+}
+
+#[test]
+fn sent_as_parsed() {
+    // the blocks before a broken end reach the reader
+    let body = paragraph("", "Before") + &table(false, &[&["a", "b"]]) + "<w:p><w:r><w:t>Broken</w:t>";
+    let mut builder = DocumentBuilder::default();
+    let result = mso_doc::parse_reader(io::Cursor::new(docx(&body)), &mso_doc::Options::default(), &mut builder);
+    assert!(result.is_err());
+    let texts: Vec<String> = builder.finish().blocks().map(|block| block.text()).collect();
+    assert_eq!(texts, vec!["Before", "a\tb"]);
+}
@@ -0,0 +1,103 @@
+extern crate mso_to_text;
+
+use std::env;
+use std::fs;
+use std::io;
+use std::process::Command;
+
+use mso_to_text::mso_doc;
+use mso_to_text::mso_doc::MsoError;
+use mso_to_text::converters::TextConverter;
+
+
+// Documents encrypted with the password "secret".
+const STANDARD: &[u8] = include_bytes!("../fuzz/seeds/parse_reader/std.docx");
+const AGILE: &[u8] = include_bytes!("../fuzz/seeds/parse_reader/agile.docx");
+const SEED: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/seeds/parse_reader/std.docx");
+
+
+fn convert(document: &[u8], password: Option<&str>) -> Result<String, MsoError> {
+    let options = mso_doc::Options { password: password.map(str::to_owned), ..mso_doc::Options::default() };
+    let mut converter = TextConverter::new(Vec::new());
+    mso_doc::parse_reader(io::Cursor::new(document), &options, &mut converter)?;
+    Ok(String::from_utf8(converter.finish().unwrap()).unwrap())
+}
+
+#[test]
+fn right_password() {
+    for document in &[STANDARD, AGILE] {
+        let text = convert(document, Some("secret")).expect("document decrypted");
+        assert!(text.contains("Hello world"), "{}", text);
+    }
+}
+
+#[test]
+fn wrong_password() {
+    for document in &[STANDARD, AGILE] {
+        match convert(document, Some("guess")) {
+            Err(MsoError::WrongPassword) => (),
+            other => panic!("expected a wrong password, got {:?}", other)
+        }
+    }
+}
+
+#[test]
+fn no_password() {
+    match convert(STANDARD, None) {
+        Err(MsoError::Encrypted) => (),
+        other => panic!("expected an encrypted document, got {:?}", other)
+    }
+
+    let status = Command::new(env!("CARGO_BIN_EXE_mso_to_text"))
+        .arg(SEED)
+        .env_remove("MSOCONV_PASSWORD")
+        .output()
+        .unwrap()
+        .status;
+    assert_eq!(status.code(), Some(4));
+}
+
+#[test]
+fn spin_count_bound() {
+    // same length as the spin count of the document, so that the
+    // compound file stays valid
+    let document = replace(AGILE, b"spinCount=\"0000001000\"", b"spinCount=\"4294967295\"");
+    match convert(&document, Some("secret")) {
+        Err(MsoError::UnsupportedEncryption(ref message)) if message.contains("spin count") => (),
+        other => panic!("expected a spin count over the maximum, got {:?}", other)
+    }
+}
+
+fn replace(data: &[u8], from: &[u8], to: &[u8]) -> Vec<u8> {
+    let start = data.windows(from.len()).position(|window| window == from).expect("pattern found");
+    let mut data = data.to_vec();
+    data[start..start + from.len()].copy_from_slice(to);
+    data
+}
+
+#[test]
+fn password_file() {
+    // every subcommand reads the password from the file
+    let path = env::temp_dir().join(format!("mso_to_text-password-{}", std::process::id()));
+    fs::write(&path, "secret\n").unwrap();
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_mso_to_text"))
+            .args(args)
+            .arg("--password-file")
+            .arg(&path)
+            .env_remove("MSOCONV_PASSWORD")
+            .output()
+            .unwrap()
+    };
+
+    let convert = run(&[SEED]);
+    let grep = run(&["grep", "-c", "Hello", SEED]);
+    let diff = run(&["diff", SEED, SEED]);
+    fs::remove_file(&path).unwrap();
+
+    assert!(String::from_utf8_lossy(&convert.stdout).contains("Hello world"));
+    assert_eq!(convert.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&grep.stdout), format!("{}:1\n", SEED));
+    assert_eq!(grep.status.code(), Some(0));
+    assert_eq!(diff.status.code(), Some(0));
+}
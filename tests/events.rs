@@ -0,0 +1,108 @@
+extern crate mso_to_text;
+
+mod common;
+
+use std::io;
+
+use mso_to_text::mso_doc;
+use mso_to_text::mso_doc::{DocEvent, Role};
+use common::*;
+
+
+fn events(document: &[u8], options: &mso_doc::Options) -> Vec<DocEvent> {
+    mso_doc::events(io::Cursor::new(document), options)
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+fn cell(text: &str) -> DocEvent {
+    DocEvent::Cell { text: text.to_owned(), style: String::new(), header: false }
+}
+
+#[test]
+fn table_in_text_box() {
+    // the table of the text box belongs to the text of its paragraph
+    let text_box = format!("<w:p><w:r><w:t>Box </w:t></w:r><w:r><w:pict><w:txbxContent>{}</w:txbxContent>\
+        </w:pict></w:r></w:p>", table(false, &[&["inside"]]));
+    let body = format!("<w:tbl><w:tr><w:tc>{}</w:tc><w:tc>{}</w:tc></w:tr><w:tr><w:tc>{}</w:tc></w:tr></w:tbl>{}",
+                       text_box, paragraph("", "b"), paragraph("", "c"), text_box);
+
+    assert_eq!(events(&docx(&body), &mso_doc::Options::default()), vec![
+        DocEvent::TableStart,
+        DocEvent::RowStart, cell("Box inside"), DocEvent::Image, cell("b"), DocEvent::RowEnd,
+        DocEvent::RowStart, cell("c"), DocEvent::RowEnd,
+        DocEvent::TableEnd,
+        DocEvent::ParagraphStart { style: String::new(), level: None, role: None },
+        DocEvent::Text("Box ".to_owned()),
+        DocEvent::Text("inside".to_owned()),
+        DocEvent::ParagraphEnd,
+        DocEvent::Image
+    ]);
+}
+
+#[test]
+fn style_filter() {
+    let body = paragraph("Heading1", "Contract")
+        + &paragraph("TOC1", "Contract ........ 1")
+        + &paragraph("Titre", "Payment")
+        + &table(false, &[&["a", "b"]])
+        + "<w:tbl><w:tr><w:tc><w:p><w:pPr><w:pStyle w:val=\"TOC2\"/></w:pPr>\
+           <w:r><w:t>left out</w:t></w:r><w:r><w:drawing/></w:r></w:p></w:tc></w:tr></w:tbl>";
+    let document = docx_with(&body, &[("styles", "styles.xml", &heading_styles())]);
+    let options = mso_doc::Options {
+        style_filter: mso_doc::StyleFilter::new(&[], &["TOC*".to_owned()]).unwrap(),
+        style_map: mso_doc::StyleMap::from_json("{\"Titre\": {\"heading\": 2}, \"heading 1\": \"quote\"}").unwrap(),
+        ..mso_doc::Options::default()
+    };
+
+    let start = |style: &str, level, role| DocEvent::ParagraphStart { style: style.to_owned(), level, role };
+    assert_eq!(events(&document, &options), vec![
+        start("Heading1", None, Some(Role::Quote)),
+        DocEvent::Text("Contract".to_owned()),
+        DocEvent::ParagraphEnd,
+        start("Titre", Some(2), None),
+        DocEvent::Text("Payment".to_owned()),
+        DocEvent::ParagraphEnd,
+        DocEvent::TableStart,
+        DocEvent::RowStart, cell("a"), cell("b"), DocEvent::RowEnd,
+        DocEvent::TableEnd,
+        DocEvent::TableStart,
+        DocEvent::RowStart,
+        DocEvent::Cell { text: String::new(), style: "TOC2".to_owned(), header: false },
+        DocEvent::RowEnd,
+        DocEvent::TableEnd
+    ]);
+}
+
+#[test]
+fn header_rows_and_sections() {
+    // the rows end with the next one, the section with its last paragraph
+    let body = table(true, &[&["Name"], &["Ada"]])
+        + "<w:p><w:pPr><w:outlineLvl w:val=\"0\"/><w:sectPr/></w:pPr><w:r><w:t>End</w:t></w:r></w:p>"
+        + &paragraph("", "");
+
+    let header = DocEvent::Cell { text: "Name".to_owned(), style: String::new(), header: true };
+    let start = |level| DocEvent::ParagraphStart { style: String::new(), level, role: None };
+    assert_eq!(events(&docx(&body), &mso_doc::Options::default()), vec![
+        DocEvent::TableStart,
+        DocEvent::RowStart, header, DocEvent::RowEnd,
+        DocEvent::RowStart, cell("Ada"), DocEvent::RowEnd,
+        DocEvent::TableEnd,
+        start(Some(1)), DocEvent::Text("End".to_owned()), DocEvent::ParagraphEnd,
+        DocEvent::SectionBreak,
+        start(None), DocEvent::ParagraphEnd
+    ]);
+}
+
+#[test]
+fn error_after_events() {
+    // the events read before a malformed part come first
+    let document = docx(&(paragraph("", "Kept") + "<w:p><w:r></w:p>"));
+    let mut events = mso_doc::events(io::Cursor::new(document), &mso_doc::Options::default());
+
+    assert_eq!(events.next().unwrap().unwrap(), DocEvent::ParagraphStart { style: String::new(), level: None, role: None });
+    assert_eq!(events.next().unwrap().unwrap(), DocEvent::Text("Kept".to_owned()));
+    assert_eq!(events.next().unwrap().unwrap(), DocEvent::ParagraphEnd);
+    assert!(events.by_ref().any(|event| event.is_err()));
+    assert!(events.next().is_none());
+}
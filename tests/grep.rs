@@ -0,0 +1,69 @@
+extern crate mso_to_text;
+
+mod common;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use common::*;
+
+
+// Runs the grep subcommand in a directory holding the documents, for the
+// output to name them by their relative path.
+fn grep(name: &str, documents: &[(&str, Vec<u8>)], args: &[&str]) -> (String, Option<i32>) {
+    let dir: PathBuf = env::temp_dir().join(format!("mso_to_text-grep-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    for (file, content) in documents {
+        fs::write(dir.join(file), content).unwrap();
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mso_to_text"))
+        .arg("grep")
+        .args(args)
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    (String::from_utf8(output.stdout).unwrap(), output.status.code())
+}
+
+fn documents() -> Vec<(&'static str, Vec<u8>)> {
+    let sale = paragraph("Title", "Sale contract")
+        + &paragraph("", "The buyer pays within 30 days.")
+        + &table(true, &[&["Delay", "Rate"], &["Over 30 days", "1.5%"]]);
+    let lease = paragraph("", "The tenant pays monthly.");
+    vec![("sale.docx", docx(&sale)), ("lease.docx", docx(&lease))]
+}
+
+#[test]
+fn matches() {
+    let (output, code) = grep("matches", &documents(), &["30 DAYS", "sale.docx", "lease.docx", "-i"]);
+    assert_golden("grep.txt", &output);
+    assert_eq!(code, Some(0));
+}
+
+#[test]
+fn files_and_counts() {
+    let (output, code) = grep("files", &documents(), &["-l", "pays", "sale.docx", "lease.docx"]);
+    assert_eq!(output, "sale.docx\nlease.docx\n");
+    assert_eq!(code, Some(0));
+
+    let (output, code) = grep("counts", &documents(), &["-c", "days", "sale.docx", "lease.docx"]);
+    assert_eq!(output, "sale.docx:2\nlease.docx:0\n");
+    assert_eq!(code, Some(0));
+}
+
+#[test]
+fn no_match_and_errors() {
+    let (output, code) = grep("none", &documents(), &["weekly", "sale.docx", "lease.docx"]);
+    assert_eq!(output, "");
+    assert_eq!(code, Some(1));
+
+    let (_, code) = grep("missing", &documents(), &["pays", "sale.docx", "missing.docx"]);
+    assert_eq!(code, Some(2));
+
+    let (_, code) = grep("pattern", &documents(), &["(", "sale.docx"]);
+    assert_eq!(code, Some(2));
+}
@@ -0,0 +1,84 @@
+extern crate mso_to_text;
+
+mod common;
+
+use std::env;
+use std::fs;
+use std::io;
+use std::process::Command;
+
+use mso_to_text::mso_doc;
+use mso_to_text::mso_doc::MsoError;
+use mso_to_text::converters::TextConverter;
+use common::*;
+
+
+fn convert(document: &[u8]) -> Result<(), MsoError> {
+    let mut converter = TextConverter::new(io::sink());
+    mso_doc::parse_reader(io::Cursor::new(document), &mso_doc::Options::default(), &mut converter)
+}
+
+// Start of a Word binary document stream: its FIB version and flags.
+fn word_document(version: u16, flags: u16) -> Vec<u8> {
+    let mut fib = vec![0u8; 0x100];
+    fib[0..2].copy_from_slice(&0xA5ECu16.to_le_bytes());
+    fib[2..4].copy_from_slice(&version.to_le_bytes());
+    fib[0x0A..0x0C].copy_from_slice(&flags.to_le_bytes());
+    fib
+}
+
+fn exit_code(name: &str, document: &[u8]) -> Option<i32> {
+    let path = env::temp_dir().join(format!("mso_to_text-legacy-{}-{}", std::process::id(), name));
+    fs::write(&path, document).unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_mso_to_text"))
+        .arg(&path)
+        .env_remove("MSOCONV_PASSWORD")
+        .output()
+        .unwrap()
+        .status;
+    fs::remove_file(&path).unwrap();
+    status.code()
+}
+
+
+#[test]
+fn legacy_formats() {
+    let documents = [
+        (compound_file(&[("Workbook", b"")]), "Excel 97-2003 workbook"),
+        (compound_file(&[("Book", b"")]), "Excel 97-2003 workbook"),
+        (compound_file(&[("Current User", b""), ("PowerPoint Document", b"")]), "PowerPoint 97-2003 presentation"),
+        (compound_file(&[("WordDocument", &word_document(0x65, 0))]), "Word 6.0/95 document")
+    ];
+    for &(ref document, kind) in &documents {
+        match convert(document) {
+            Err(MsoError::LegacyFormat(found)) => assert_eq!(found, kind),
+            other => panic!("expected a {}, got {:?}", kind, other)
+        }
+    }
+    assert_eq!(exit_code("book.xls", &documents[0].0), Some(3));
+}
+
+#[test]
+fn encrypted() {
+    let documents = [
+        // Word 97-2003 document with its encryption flag
+        compound_file(&[("WordDocument", &word_document(0xC1, 0x0100)), ("1Table", b"")]),
+        // OOXML package, without the password to decrypt it
+        compound_file(&[("EncryptionInfo", b""), ("EncryptedPackage", b"")])
+    ];
+    for document in &documents {
+        match convert(document) {
+            Err(MsoError::Encrypted) => (),
+            other => panic!("expected an encrypted document, got {:?}", other)
+        }
+    }
+    assert_eq!(exit_code("encrypted.doc", &documents[0]), Some(4));
+}
+
+#[test]
+fn other_compound_files() {
+    match convert(&compound_file(&[("Contents", b"")])) {
+        Err(MsoError::UnsupportedContent(_)) => (),
+        other => panic!("expected an unsupported content, got {:?}", other)
+    }
+}
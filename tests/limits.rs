@@ -0,0 +1,130 @@
+extern crate mso_to_text;
+
+mod common;
+
+use std::io;
+
+use mso_to_text::mso_doc;
+use mso_to_text::mso_doc::{Limits, MsoError};
+use mso_to_text::converters::TextConverter;
+use common::*;
+
+
+fn convert(document: &[u8], limits: Limits) -> Result<(), MsoError> {
+    let options = mso_doc::Options { limits, ..mso_doc::Options::default() };
+    let mut converter = TextConverter::new(io::sink());
+    mso_doc::parse_reader(io::Cursor::new(document), &options, &mut converter)
+}
+
+fn assert_exceeded(result: Result<(), MsoError>, reason: &str) {
+    match result {
+        Err(MsoError::LimitExceeded(ref message)) if message.contains(reason) => (),
+        other => panic!("expected a limit on {}, got {:?}", reason, other)
+    }
+}
+
+
+#[test]
+fn part_size() {
+    let document = docx(&paragraph("", &"a".repeat(1000)));
+    let limits = Limits { max_part_size: 500, ..Limits::default() };
+    assert_exceeded(convert(&document, limits), "part /word/document.xml over 500 bytes");
+}
+
+#[test]
+fn compression_ratio() {
+    let document = docx(&paragraph("", &"a".repeat(4 << 20)));
+    let limits = Limits { max_compression_ratio: 10, ..Limits::default() };
+    assert_exceeded(convert(&document, limits), "compressed over 10 times");
+    assert!(convert(&document, Limits::default()).is_err());
+}
+
+#[test]
+fn small_parts_ignore_ratio() {
+    let document = docx(&paragraph("", &"a".repeat(100_000)));
+    let limits = Limits { max_compression_ratio: 10, ..Limits::default() };
+    convert(&document, limits).expect("document converted");
+}
+
+#[test]
+fn parts() {
+    let document = docx(&paragraph("", "text"));
+    let limits = Limits { max_parts: 3, ..Limits::default() };
+    assert_exceeded(convert(&document, limits), "4 parts, over 3");
+}
+
+#[test]
+fn depth() {
+    let body = format!("<w:p>{}{}</w:p>", "<w:r>".repeat(50), "</w:r>".repeat(50));
+    let limits = Limits { max_depth: 20, ..Limits::default() };
+    assert_exceeded(convert(&docx(&body), limits), "nested over 20 levels");
+}
+
+#[test]
+fn paragraph_length() {
+    let limits = Limits { max_paragraph_length: 50, ..Limits::default() };
+    let body = paragraph("", &"a".repeat(40)) + &paragraph("", &"b".repeat(40));
+    convert(&docx(&body), limits.clone()).expect("document converted");
+
+    // text boxes add to the text of their paragraph
+    let body = format!("<w:p><w:r><w:t>{}</w:t><w:txbxContent>{}</w:txbxContent></w:r></w:p>",
+                       "a".repeat(40), paragraph("", &"b".repeat(40)));
+    assert_exceeded(convert(&docx(&body), limits), "paragraph over 50 bytes");
+}
+
+#[test]
+fn odt_repeated_spaces() {
+    let limits = Limits { max_paragraph_length: 50, ..Limits::default() };
+    let body = "<office:text><text:p>a<text:s text:c=\"20\"/>b</text:p></office:text>";
+    convert(&opendocument("application/vnd.oasis.opendocument.text", body), limits.clone())
+        .expect("document converted");
+
+    // the spaces count in the text of the paragraph, whatever their number
+    let body = format!("<office:text><text:p>{}<text:s text:c=\"200000000\"/></text:p></office:text>",
+                       "<text:s text:c=\"200000000\"/>".repeat(4));
+    assert_exceeded(convert(&opendocument("application/vnd.oasis.opendocument.text", &body), limits.clone()),
+                    "paragraph over 50 bytes");
+    let body = format!("<office:text><text:p><text:s text:c=\"30\"/>{}</text:p></office:text>", "a".repeat(30));
+    assert_exceeded(convert(&opendocument("application/vnd.oasis.opendocument.text", &body), limits),
+                    "paragraph over 50 bytes");
+}
+
+#[test]
+fn ods_repeated_cells() {
+    let limits = Limits { max_cells: 1000, ..Limits::default() };
+    let sheet = |rows: usize, columns: usize| format!(
+        "<office:spreadsheet><table:table table:name=\"Sheet1\">\
+        <table:table-row table:number-rows-repeated=\"{}\">\
+        <table:table-cell table:number-columns-repeated=\"{}\"><text:p>x</text:p></table:table-cell>\
+        </table:table-row></table:table></office:spreadsheet>", rows, columns);
+    let ods = "application/vnd.oasis.opendocument.spreadsheet";
+    convert(&opendocument(ods, &sheet(10, 100)), limits.clone()).expect("document converted");
+    assert_exceeded(convert(&opendocument(ods, &sheet(11, 100)), limits.clone()), "over 1000 cells");
+
+    // empty rows count as they are sent before a non empty one
+    let empty_rows = |rows: usize| format!(
+        "<office:spreadsheet><table:table table:name=\"Sheet1\">\
+        <table:table-row table:number-rows-repeated=\"{}\"><table:table-cell/></table:table-row>\
+        <table:table-row><table:table-cell><text:p>x</text:p></table:table-cell></table:table-row>\
+        </table:table></office:spreadsheet>", rows);
+    convert(&opendocument(ods, &empty_rows(999)), limits.clone()).expect("document converted");
+    assert_exceeded(convert(&opendocument(ods, &empty_rows(1000)), limits), "over 1000 cells");
+
+    // billions of cells by default
+    assert_exceeded(convert(&opendocument(ods, &sheet(1_048_576, 16_384)), Limits::default()), "cells");
+}
+
+#[test]
+fn events() {
+    let body = format!("{}<w:p>{}{}</w:p>", paragraph("", "first"), "<w:r>".repeat(50), "</w:r>".repeat(50));
+    let options = mso_doc::Options {
+        limits: Limits { max_depth: 20, ..Limits::default() },
+        ..mso_doc::Options::default()
+    };
+    let events: Vec<_> = mso_doc::events(io::Cursor::new(docx(&body)), &options).collect();
+    assert!(events.iter().any(|event| event.is_ok()));
+    match events.last() {
+        Some(&Err(MsoError::LimitExceeded(_))) => (),
+        other => panic!("expected a limit on the depth, got {:?}", other)
+    }
+}
@@ -0,0 +1,67 @@
+extern crate mso_to_text;
+
+mod common;
+
+use common::*;
+
+
+const ODT: &str = "application/vnd.oasis.opendocument.text";
+const ODS: &str = "application/vnd.oasis.opendocument.spreadsheet";
+
+
+#[test]
+fn odt_headings_and_lists() {
+    let body = "<office:automatic-styles>\
+        <style:style style:name=\"P1\" style:parent-style-name=\"Heading_20_2\"/>\
+        </office:automatic-styles>\
+        <office:text>\
+        <text:h text:style-name=\"Heading_20_1\" text:outline-level=\"1\">Contract</text:h>\
+        <text:p>Terms<text:s text:c=\"3\"/>and<text:tab/>conditions</text:p>\
+        <text:h text:style-name=\"P1\" text:outline-level=\"2\">Payment</text:h>\
+        <text:list><text:list-item><text:p>first item</text:p></text:list-item>\
+        <text:list-item><text:p>second item</text:p>\
+        <text:list><text:list-item><text:p>nested item</text:p></text:list-item></text:list>\
+        </text:list-item></text:list>\
+        </office:text>";
+    assert_all_golden("odt_headings_and_lists", &opendocument(ODT, body));
+}
+
+#[test]
+fn odt_tables() {
+    let body = "<office:text><text:p>Before</text:p>\
+        <table:table table:name=\"Table1\">\
+        <table:table-header-rows><table:table-row>\
+        <table:table-cell><text:p>Name</text:p></table:table-cell>\
+        <table:table-cell><text:p>Value</text:p></table:table-cell>\
+        </table:table-row></table:table-header-rows>\
+        <table:table-row><table:table-cell><text:p>x, y</text:p></table:table-cell>\
+        <table:table-cell/></table:table-row>\
+        </table:table><text:p>After</text:p></office:text>";
+    assert_all_golden("odt_tables", &opendocument(ODT, body));
+}
+
+#[test]
+fn ods_repeated() {
+    // repeated cells and rows expanded, trailing empty ones dropped, and
+    // each sheet in its own section
+    let body = "<office:spreadsheet><table:table table:name=\"Prices\">\
+        <table:table-header-rows><table:table-row>\
+        <table:table-cell><text:p>Item</text:p></table:table-cell>\
+        <table:table-cell><text:p>Price</text:p></table:table-cell>\
+        </table:table-row></table:table-header-rows>\
+        <table:table-row table:number-rows-repeated=\"2\">\
+        <table:table-cell table:number-columns-repeated=\"2\"><text:p>x</text:p></table:table-cell>\
+        <table:table-cell table:number-columns-repeated=\"1000\"/>\
+        </table:table-row>\
+        <table:table-row table:number-rows-repeated=\"2\"><table:table-cell/></table:table-row>\
+        <table:table-row><table:table-cell table:number-columns-repeated=\"2\"/>\
+        <table:table-cell office:value=\"1.5\"/></table:table-row>\
+        <table:table-row table:number-rows-repeated=\"1048000\">\
+        <table:table-cell table:number-columns-repeated=\"16384\"/></table:table-row>\
+        </table:table>\
+        <table:table table:name=\"Notes\"><table:table-row>\
+        <table:table-cell><text:p>first</text:p><text:p>second</text:p></table:table-cell>\
+        </table:table-row></table:table>\
+        </office:spreadsheet>";
+    assert_all_golden("ods_repeated", &opendocument(ODS, body));
+}
@@ -0,0 +1,48 @@
+extern crate mso_to_text;
+
+mod common;
+
+use std::io;
+
+use mso_to_text::mso_doc;
+use common::*;
+
+
+#[test]
+fn headings() {
+    let body = paragraph("Title", "Contract")
+        + &paragraph("Heading1", "Parties")
+        + &paragraph("Normal", "Between us")
+        + &paragraph("Heading1", "Payment")
+        + &paragraph("Heading2", "Terms")
+        + &table(false, &[&["a", "b"]])
+        + &paragraph("MyHeading", "Late payment")
+        + &paragraph("Heading3", "Interests")
+        + "<w:p><w:pPr><w:pStyle w:val=\"Heading1\"/><w:outlineLvl w:val=\"9\"/></w:pPr>\
+           <w:r><w:t>Body text</w:t></w:r></w:p>"
+        + "<w:p><w:pPr><w:outlineLvl w:val=\"0\"/></w:pPr><w:r><w:t>Annexes</w:t></w:r></w:p>"
+        + &paragraph("Heading3", "Skipping a level");
+    let document = docx_with(&body, &[("styles", "styles.xml", &heading_styles())]);
+    assert_all_golden("outline", &document);
+    assert_golden("outline_toc.txt", &to_outline(&document));
+    assert_golden("outline_toc.json", &to_outline_json(&document));
+}
+
+#[test]
+fn without_styles() {
+    let document = docx(&(paragraph("Heading1", "Not a heading") + &paragraph("", "Text")));
+    assert_golden("outline_without_styles.txt", &to_outline(&document));
+}
+
+#[test]
+fn event_levels() {
+    let body = paragraph("Heading2", "Terms") + &paragraph("", "Text");
+    let document = docx_with(&body, &[("styles", "styles.xml", &heading_styles())]);
+    let levels: Vec<Option<u32>> = mso_doc::events(io::Cursor::new(document), &mso_doc::Options::default())
+        .filter_map(|event| match event.unwrap() {
+            mso_doc::DocEvent::ParagraphStart { level, .. } => Some(level),
+            _ => None
+        })
+        .collect();
+    assert_eq!(levels, vec![Some(2), None]);
+}
@@ -0,0 +1,32 @@
+extern crate mso_to_text;
+
+use std::io::Write;
+
+use mso_to_text::output::{Encoder, Newline};
+
+
+fn encode(writes: &[&str], newline: Newline, bom: bool) -> Vec<u8> {
+    let mut out = Vec::new();
+    {
+        let mut encoder = Encoder::new(&mut out, newline, bom);
+        for text in writes {
+            encoder.write_all(text.as_bytes()).unwrap();
+        }
+    }
+    out
+}
+
+#[test]
+fn crlf() {
+    assert_eq!(encode(&["a\nb\n", "c"], Newline::Crlf, false), b"a\r\nb\r\nc");
+    assert_eq!(encode(&["a\r\nb"], Newline::Crlf, false), b"a\r\nb");
+    // a line ending split between writes
+    assert_eq!(encode(&["a\r", "\nb"], Newline::Crlf, false), b"a\r\nb");
+    assert_eq!(encode(&["a", "\nb"], Newline::Crlf, false), b"a\r\nb");
+}
+
+#[test]
+fn lf_and_bom() {
+    assert_eq!(encode(&["a\r\nb\n"], Newline::Lf, false), b"a\r\nb\n");
+    assert_eq!(encode(&["a\n", "b"], Newline::Lf, true), b"\xEF\xBB\xBFa\nb");
+}
@@ -0,0 +1,36 @@
+extern crate mso_to_text;
+
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use common::*;
+
+
+fn seed(name: &str) -> Vec<u8> {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "fuzz", "seeds", "parse_reader", name].iter().collect();
+    fs::read(path).unwrap()
+}
+
+#[test]
+fn document() {
+    // code pages of the document and of the fonts, \u with negative values
+    // after \uc2, a header row, and destinations left out
+    assert_all_golden("rtf", &seed("doc.rtf"));
+}
+
+#[test]
+fn sections() {
+    assert_all_golden("rtf_sections", &seed("sect.rtf"));
+}
+
+#[test]
+fn style_entry_groups() {
+    // groups nested in a style sheet entry keep its name
+    let document = "{\\rtf1{\\stylesheet{\\s0 Normal;}{\\s1{\\*\\keycode \\shift\\ctrl 1}\\outlinelevel0 Heading 1;}\
+        {\\s2\\sbasedon0{\\*\\keycode \\shift\\ctrl 2} Quote;}}\
+        \\pard\\s1 Title\\par\\pard\\s2 Cited\\par\\pard\\s0 Text\\par}";
+    assert_eq!(to_text(document.as_bytes()), "Heading 1\tTitle\nQuote\tCited\nNormal\tText\n");
+    assert_eq!(to_outline(document.as_bytes()), "1. Title\n");
+}
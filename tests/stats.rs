@@ -0,0 +1,77 @@
+extern crate mso_to_text;
+
+mod common;
+
+use std::io;
+
+use mso_to_text::mso_doc;
+use mso_to_text::stats::{self, StatsCounter};
+use common::*;
+
+
+fn report(document: &[u8]) -> String {
+    let mut counter = StatsCounter::default();
+    parse(document, &mut counter);
+    let report = stats::Report {
+        counted: counter.finish(),
+        saved: mso_doc::saved_counts(io::Cursor::new(document), &mso_doc::Options::default()).unwrap()
+    };
+    let mut out = Vec::new();
+    stats::write_text(&report, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn counts() {
+    let drawing = "<w:p><w:r><w:drawing/></w:r><w:r><w:t>Figure 1</w:t></w:r></w:p>";
+    // a drawing with its VML fallback is a single image
+    let alternate = "<w:p><w:r><mc:AlternateContent \
+        xmlns:mc=\"http://schemas.openxmlformats.org/markup-compatibility/2006\">\
+        <mc:Choice Requires=\"wps\"><w:drawing/></mc:Choice>\
+        <mc:Fallback><w:pict/></mc:Fallback></mc:AlternateContent></w:r></w:p>";
+    let body = paragraph("Heading1", "Payment terms")
+        + &paragraph("", "The buyer pays 1,500.00 EUR within 30 days, doesn't he?")
+        + &paragraph("", "")
+        + &paragraph("", "契約書の条件")
+        + &table(true, &[&["Delay", "Rate"], &["30 days", "1.5%"]])
+        + drawing
+        + alternate;
+    let footnotes = format!(
+        "<?xml version=\"1.0\"?><w:footnotes xmlns:w=\"{}\">\
+        <w:footnote w:id=\"1\">{}</w:footnote><w:footnote w:id=\"2\">{}</w:footnote></w:footnotes>",
+        W_NAMESPACE, paragraph("", "A footnote"), paragraph("", "Another one"));
+    let app = "<?xml version=\"1.0\"?>\
+        <Properties xmlns=\"http://schemas.openxmlformats.org/officeDocument/2006/extended-properties\">\
+        <Pages>1</Pages><Words>20</Words><Characters>110</Characters><Lines>4</Lines>\
+        <Paragraphs>6</Paragraphs><CharactersWithSpaces>128</CharactersWithSpaces></Properties>";
+    let document = docx_package(
+        &body,
+        &[("styles", "styles.xml", &heading_styles()), ("footnotes", "footnotes.xml", &footnotes)],
+        &[("extended-properties", "docProps/app.xml", app)]);
+    assert_golden("stats.txt", &report(&document));
+}
+
+#[test]
+fn without_saved_counts() {
+    assert_golden("stats_without_saved.txt", &report(&docx(&paragraph("", "Two words"))));
+}
+
+#[test]
+fn cells_of_several_paragraphs() {
+    let counts = |document: &[u8]| {
+        let mut counter = StatsCounter::default();
+        parse(document, &mut counter);
+        let stats = counter.finish();
+        (stats.tables, stats.cells, stats.words)
+    };
+
+    // a cell counts once, whatever its paragraphs
+    let docx_cells = format!("<w:tbl><w:tr><w:tc>{}{}</w:tc><w:tc>{}</w:tc></w:tr></w:tbl>",
+                             paragraph("", "First line"), paragraph("", "second"), paragraph("", ""));
+    assert_eq!(counts(&docx(&docx_cells)), (1, 2, 3));
+
+    let odt_cells = "<office:text><table:table><table:table-row>\
+        <table:table-cell><text:p>First line</text:p><text:p>second</text:p></table:table-cell>\
+        <table:table-cell/></table:table-row></table:table></office:text>";
+    assert_eq!(counts(&opendocument("application/vnd.oasis.opendocument.text", odt_cells)), (1, 2, 3));
+}
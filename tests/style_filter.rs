@@ -0,0 +1,47 @@
+extern crate mso_to_text;
+
+mod common;
+
+use std::io;
+
+use mso_to_text::mso_doc;
+use mso_to_text::converters::TextConverter;
+use common::*;
+
+
+fn filtered(document: &[u8], include: &[&str], exclude: &[&str]) -> String {
+    let strings = |patterns: &[&str]| patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+    let options = mso_doc::Options {
+        style_filter: mso_doc::StyleFilter::new(&strings(include), &strings(exclude)).unwrap(),
+        ..mso_doc::Options::default()
+    };
+    let mut converter = TextConverter::new(Vec::new());
+    mso_doc::parse_reader(io::Cursor::new(document), &options, &mut converter).unwrap();
+    String::from_utf8(converter.finish().unwrap()).unwrap()
+}
+
+fn document() -> Vec<u8> {
+    let body = paragraph("Heading1", "Contract")
+        + &paragraph("TOC1", "Contract ........ 1")
+        + &paragraph("", "The buyer pays within 30 days.")
+        + &paragraph("Caption", "Figure 1: delays")
+        + &paragraph("MyHeading", "Payment")
+        + &table(true, &[&["Delay", "Rate"], &["30 days", "1.5%"]]);
+    docx_with(&body, &[("styles", "styles.xml", &heading_styles())])
+}
+
+#[test]
+fn include() {
+    // by name, as "heading 1" and "My heading"
+    assert_golden("style_filter_include.txt", &filtered(&document(), &["*heading*"], &[]));
+}
+
+#[test]
+fn exclude() {
+    assert_golden("style_filter_exclude.txt", &filtered(&document(), &[], &["caption", "TOC?"]));
+}
+
+#[test]
+fn bad_pattern() {
+    assert!(mso_doc::StyleFilter::new(&["[".to_owned()], &[]).is_err());
+}
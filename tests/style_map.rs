@@ -0,0 +1,98 @@
+extern crate mso_to_text;
+
+mod common;
+
+use std::io;
+
+use mso_to_text::mso_doc;
+use mso_to_text::document::{Block, DocumentBuilder};
+use mso_to_text::converters::TextConverter;
+use mso_to_text::outline;
+use common::*;
+
+
+fn build(document: &[u8], style_map: mso_doc::StyleMap) -> DocumentBuilder {
+    let options = mso_doc::Options { style_map, ..mso_doc::Options::default() };
+    let mut builder = DocumentBuilder::default();
+    mso_doc::parse_reader(io::Cursor::new(document), &options, &mut builder).unwrap();
+    builder
+}
+
+fn outline(document: &[u8], style_map: mso_doc::StyleMap) -> String {
+    let mut out = Vec::new();
+    outline::write_text(&outline::headings(&build(document, style_map).finish()), &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+// Client template without heading styles.
+fn document() -> Vec<u8> {
+    let styles = format!("<?xml version=\"1.0\"?><w:styles xmlns:w=\"{}\">\
+        <w:style w:type=\"paragraph\" w:styleId=\"Titre1\"><w:name w:val=\"Titre 1\"/></w:style>\
+        <w:style w:type=\"paragraph\" w:styleId=\"H2Corp\"><w:name w:val=\"H2-Corp\"/></w:style>\
+        <w:style w:type=\"paragraph\" w:styleId=\"Heading1\"><w:name w:val=\"heading 1\"/></w:style>\
+        </w:styles>", W_NAMESPACE);
+    let body = paragraph("Heading1", "Table of contents")
+        + &paragraph("Titre1", "Contract")
+        + &paragraph("H2Corp", "Payment")
+        + &paragraph("Citation", "As agreed.")
+        + &paragraph("Titre1", "Annexes");
+    docx_with(&body, &[("styles", "styles.xml", &styles)])
+}
+
+#[test]
+fn json() {
+    let style_map = mso_doc::StyleMap::from_json(
+        "{\"titre1\": {\"heading\": 1}, \"H2-Corp\": {\"heading\": 2}, \"Heading1\": \"ignore\", \"Citation\": \"quote\"}")
+        .unwrap();
+    assert_golden("style_map.txt", &outline(&document(), style_map));
+}
+
+#[test]
+fn toml() {
+    let style_map = mso_doc::StyleMap::from_toml(
+        "Titre1 = { heading = 1 }\n\"h2-corp\" = { heading = 2 }\n\"heading 1\" = \"ignore\"\n")
+        .unwrap();
+    assert_golden("style_map.txt", &outline(&document(), style_map));
+}
+
+#[test]
+fn demoted_heading() {
+    let style_map = mso_doc::StyleMap::from_json("{\"Heading1\": \"caption\"}").unwrap();
+    assert_eq!(outline(&document(), style_map), "");
+}
+
+#[test]
+fn roles() {
+    let style_map = mso_doc::StyleMap::from_json("{\"Heading1\": \"caption\", \"Citation\": \"quote\"}").unwrap();
+    let roles: Vec<(String, Option<mso_doc::Role>)> = build(&document(), style_map).finish().paragraphs()
+        .map(|block| match *block {
+            Block::Paragraph { ref text, role, .. } => (text.clone(), role),
+            Block::Table { .. } => unreachable!()
+        })
+        .collect();
+    assert_eq!(roles, vec![
+        ("Table of contents".to_owned(), Some(mso_doc::Role::Caption)),
+        ("Contract".to_owned(), None),
+        ("Payment".to_owned(), None),
+        ("As agreed.".to_owned(), Some(mso_doc::Role::Quote)),
+        ("Annexes".to_owned(), None)
+    ]);
+}
+
+#[test]
+fn bad_maps() {
+    assert!(mso_doc::StyleMap::from_json("{\"Titre1\": {\"heading\": 10}}").is_err());
+    assert!(mso_doc::StyleMap::from_json("{\"Titre1\": \"title\"}").is_err());
+    assert!(mso_doc::StyleMap::from_toml("Titre1 = 1").is_err());
+}
+
+#[test]
+fn text() {
+    let style_map = mso_doc::StyleMap::from_json(
+        "{\"Titre1\": {\"heading\": 1}, \"H2-Corp\": \"list\", \"Heading1\": \"caption\", \"Citation\": \"quote\"}")
+        .unwrap();
+    let options = mso_doc::Options { style_map, ..mso_doc::Options::default() };
+    let mut converter = TextConverter::new(Vec::new());
+    mso_doc::parse_reader(io::Cursor::new(document()), &options, &mut converter).unwrap();
+    assert_golden("style_map_text.txt", &String::from_utf8(converter.finish().unwrap()).unwrap());
+}